@@ -0,0 +1,34 @@
+//! A minimal external agent, run it with `mbw -s -a "cargo run --example party_agent"`.
+//!
+//! Every line on its standard input is an observation of the game as JSON, and every line
+//! it writes back is the list of commands it wants to carry out. On the side of the virus it
+//! keeps throwing parties for the first person it sees, as the president it does nothing.
+
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+
+fn main() -> io::Result<()> {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+
+    for line in stdin.lock().lines() {
+        let observation: Value = serde_json::from_str(&line?)?;
+
+        // `side` is true for the virus, people are keyed by their id
+        let party = observation["world"]["people"]
+            .as_object()
+            .and_then(|people| people.keys().next())
+            .and_then(|id| id.parse::<u32>().ok())
+            .filter(|_| observation["side"] == true);
+
+        let commands = match party {
+            Some(id) => json!([{ "PartyImpulse": id }]),
+            None => json!([]),
+        };
+
+        writeln!(stdout, "{}", commands)?;
+        stdout.flush()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,302 @@
+//! Agents play a side of the game in place of a connected player. Each one is handed the
+//! same observation a client of its side would have, and answers with the commands it wants
+//! to carry out. Agents can live in the server, like the bots, or in a program of their own.
+
+use crate::bot::{Bot, Difficulty};
+use crate::config::Config;
+use crate::research::Upgrade;
+use crate::server::{CommandKind, GameSession, Player, PlayerCommand, PlayerUpdate};
+use crate::virus::StrainId;
+use crate::world::World;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, BufWriter, ErrorKind, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+use tokio::task;
+
+/// How long an external agent gets to answer an observation before it is considered stopped.
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Everything a player on one side knows about the game.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Observation {
+    pub tick: u64,
+    pub side: bool,
+    pub money: u32,
+    /// Tick at which each kind of command can be used again
+    pub cooldowns: HashMap<CommandKind, u64>,
+    pub upgrades: HashSet<Upgrade>,
+    /// Tick at which the current booster campaign ends
+    pub booster_campaign: u64,
+    pub config: Config,
+    /// The world as the side sees it, the president only knows about people that have tested
+    /// positive, and not which strain they have.
+    pub world: World,
+}
+
+impl Observation {
+    pub fn new(session: &GameSession, player: &Player) -> Self {
+        let player_session = session.player(player);
+        let side = player_session.side;
+        let mut world = session.world.clone();
+
+        if !side {
+            for person in world.people.values_mut() {
+                person.strain = if person.tested_positive() {
+                    Some(StrainId::UNKNOWN)
+                } else {
+                    None
+                };
+                person.tick_infected = 0;
+            }
+
+            for contamination in world.map.contamination.iter_mut().flatten() {
+                contamination.strain = Default::default();
+            }
+        }
+
+        Self {
+            tick: session.tick_count,
+            side,
+            money: player_session.money,
+            cooldowns: player_session.cooldowns.clone(),
+            upgrades: player_session.upgrades.clone(),
+            booster_campaign: session.booster_campaign,
+            config: session.config.clone(),
+            world,
+        }
+    }
+
+    pub fn is_ready(&self, kind: CommandKind) -> bool {
+        self.cooldowns.get(&kind).copied().unwrap_or(0) <= self.tick
+    }
+}
+
+/// Decides what to do from what its side can see. Commands that can't be afforded or are
+/// still cooling down are ignored by the session, just like they are for players.
+pub trait Agent: Send {
+    fn act(&mut self, observation: &Observation) -> Vec<PlayerCommand>;
+
+    /// Ticks between every observation the agent is given.
+    fn interval(&self) -> u64 {
+        1
+    }
+}
+
+/// Who takes the place of a player that isn't connected.
+#[derive(Clone, Debug)]
+pub enum Seat {
+    Bot(Difficulty),
+    /// Command that starts an external agent
+    External(String),
+}
+
+impl Seat {
+    pub fn agent(&self, side: bool) -> std::io::Result<Box<dyn Agent>> {
+        Ok(match self {
+            Seat::Bot(difficulty) => Box::new(Bot::new(side, *difficulty)),
+            Seat::External(command) => Box::new(ExternalAgent::spawn(command)?),
+        })
+    }
+}
+
+/// A seat in a session that is played by an agent.
+pub struct AgentPlayer {
+    player: Player,
+    side: bool,
+    /// Gone if the agent panicked, the seat forfeits its moves for the rest of the game
+    agent: Option<Box<dyn Agent>>,
+    sender: Sender<PlayerUpdate>,
+    /// Tick of the next observation
+    next_observation: u64,
+}
+
+impl AgentPlayer {
+    pub fn new(
+        player: Player,
+        side: bool,
+        agent: Box<dyn Agent>,
+        sender: Sender<PlayerUpdate>,
+    ) -> Self {
+        Self {
+            player,
+            side,
+            agent: Some(agent),
+            sender,
+            next_observation: 0,
+        }
+    }
+
+    /// What the agent gets to see of the session, if it is time for it to observe it.
+    pub fn observe(&mut self, session: &GameSession) -> Option<Observation> {
+        let agent = self.agent.as_ref()?;
+
+        if session.tick_count < self.next_observation {
+            return None;
+        }
+
+        self.next_observation = session.tick_count + agent.interval().max(1);
+
+        Some(Observation::new(session, &self.player))
+    }
+
+    /// Lets the agent act on the observation and passes its commands on. The agent is run off
+    /// the runtime, as it may take its time or wait for a program of its own to answer.
+    pub async fn act(&mut self, observation: Observation) {
+        let mut agent = match self.agent.take() {
+            Some(agent) => agent,
+            None => return,
+        };
+
+        let commands = match task::spawn_blocking(move || {
+            let commands = agent.act(&observation);
+            (agent, commands)
+        })
+        .await
+        {
+            Ok((agent, commands)) => {
+                self.agent = Some(agent);
+                commands
+            }
+            Err(error) => {
+                eprintln!("Agent crashed: {}", error);
+                return;
+            }
+        };
+
+        for command in commands {
+            self.sender
+                .send(PlayerUpdate {
+                    side: self.side,
+                    command,
+                    player: self.player.clone(),
+                })
+                .unwrap();
+        }
+    }
+}
+
+/// An agent running as a program of its own. The program is talked to with one line of JSON
+/// at a time: every [`Observation`] is written to its standard input, and it answers on its
+/// standard output with a list of [`PlayerCommand`]s, like
+///
+/// ```text
+/// [{"PartyImpulse": 12}, {"Roadblock": {"x": 4, "y": 9}}, "EconomicCrash"]
+/// ```
+///
+/// See `examples/party_agent.rs` for an agent that does just that. The program is talked to on
+/// a thread of its own, so one that hangs can't hold up the game.
+pub struct ExternalAgent {
+    child: Child,
+    /// Observations for the thread talking to the program
+    observations: Sender<String>,
+    /// Its answers, or why there won't be any more
+    answers: Receiver<Result<String, String>>,
+    /// Set once the program stops answering
+    stopped: bool,
+}
+
+impl ExternalAgent {
+    /// Starts the program, *command* is split on whitespace into the program and its
+    /// arguments.
+    pub fn spawn(command: &str) -> std::io::Result<Self> {
+        let mut parts = command.split_whitespace();
+        let program = parts.next().unwrap_or_default();
+
+        let mut child = Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        let mut input = BufWriter::new(child.stdin.take().unwrap());
+        let mut output = BufReader::new(child.stdout.take().unwrap());
+        let (observations, observation_receiver) = mpsc::channel::<String>();
+        let (answer_sender, answers) = mpsc::channel();
+
+        // ends once the agent is dropped, or the program can't be talked to anymore
+        thread::spawn(move || {
+            for line in observation_receiver {
+                let answer =
+                    Self::exchange(&mut input, &mut output, &line).map_err(|e| e.to_string());
+                let failed = answer.is_err();
+
+                if answer_sender.send(answer).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            child,
+            observations,
+            answers,
+            stopped: false,
+        })
+    }
+
+    fn exchange(
+        input: &mut impl Write,
+        output: &mut impl BufRead,
+        line: &str,
+    ) -> std::io::Result<String> {
+        writeln!(input, "{}", line)?;
+        input.flush()?;
+
+        let mut answer = String::new();
+        if output.read_line(&mut answer)? == 0 {
+            return Err(std::io::Error::new(
+                ErrorKind::UnexpectedEof,
+                "the program closed its output",
+            ));
+        }
+
+        Ok(answer)
+    }
+
+    fn ask(
+        &mut self,
+        observation: &Observation,
+    ) -> Result<Vec<PlayerCommand>, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        self.observations
+            .send(serde_json::to_string(observation)?)?;
+
+        let answer = match self.answers.recv_timeout(ANSWER_TIMEOUT) {
+            Ok(answer) => answer?,
+            Err(RecvTimeoutError::Timeout) => {
+                return Err(format!("no answer within {:?}", ANSWER_TIMEOUT).into())
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        Ok(serde_json::from_str(&answer)?)
+    }
+}
+
+impl Agent for ExternalAgent {
+    fn act(&mut self, observation: &Observation) -> Vec<PlayerCommand> {
+        // an agent that stopped answering forfeits its moves, the game goes on without it
+        if self.stopped {
+            return Vec::new();
+        }
+
+        match self.ask(observation) {
+            Ok(commands) => commands,
+            Err(error) => {
+                eprintln!("Agent stopped answering: {}", error);
+                self.stopped = true;
+                Vec::new()
+            }
+        }
+    }
+}
+
+impl Drop for ExternalAgent {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
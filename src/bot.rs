@@ -0,0 +1,363 @@
+//! A computer player that can take either side. It is an agent like any other, so it only
+//! looks at what a player on its side would be able to see.
+
+use crate::agent::{Agent, Observation};
+use crate::map::{Map, Position, Tile};
+use crate::person::PersonId;
+use crate::research::Upgrade;
+use crate::server::PlayerCommand;
+use crate::spatial::SpatialIndex;
+use crate::virus::Mutation;
+use crate::world::Location;
+use rand::prelude::*;
+use std::str::FromStr;
+
+/// Radius in tiles that counts as the same cluster of cases.
+const CLUSTER_RADIUS: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    /// Ticks between every move the bot makes.
+    fn think_ticks(&self) -> u64 {
+        match self {
+            Difficulty::Easy => 180,
+            Difficulty::Normal => 60,
+            Difficulty::Hard => 20,
+        }
+    }
+
+    /// Chance of the bot planning its move, instead of doing something at random.
+    fn insight(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.3,
+            Difficulty::Normal => 0.7,
+            Difficulty::Hard => 1.0,
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(format!(
+                "unknown difficulty {}, use easy, normal or hard",
+                s
+            )),
+        }
+    }
+}
+
+pub struct Bot {
+    side: bool,
+    difficulty: Difficulty,
+    rng: StdRng,
+}
+
+impl Bot {
+    pub fn new(side: bool, difficulty: Difficulty) -> Self {
+        Self {
+            side,
+            difficulty,
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// Moves for the president, best first. Only cases that have tested positive are known.
+    fn president_moves(&mut self, observation: &Observation) -> Vec<PlayerCommand> {
+        let world = &observation.world;
+        let map = &world.map;
+        let rng = &mut self.rng;
+        let mut moves = Vec::new();
+        let test_centers = tiles(map, Tile::TestCenter);
+        let vaccine_centers = tiles(map, Tile::VaccineCenter);
+
+        let cases = world
+            .people
+            .values()
+            .filter(|person| person.alive && person.tested_positive())
+            .map(|person| person.position.clone())
+            .collect::<Vec<_>>();
+
+        // before any cases are known, the busiest place is the best guess
+        let everyone = world
+            .people
+            .values()
+            .filter(|person| person.alive)
+            .map(|person| person.position.clone())
+            .collect::<Vec<_>>();
+        let hotspot = cluster(&cases).or_else(|| cluster(&everyone));
+
+        if let Some(hotspot) = &hotspot {
+            let covered = test_centers
+                .iter()
+                .any(|center| distance(center, hotspot) <= CLUSTER_RADIUS * 2);
+
+            if !covered {
+                moves.extend(
+                    empty_near(map, hotspot, CLUSTER_RADIUS, rng).map(PlayerCommand::Testcenter),
+                );
+            }
+        }
+
+        // lock down the homes of the known cases
+        let home = world
+            .people
+            .values()
+            .filter(|person| person.alive && person.tested_positive())
+            .map(|person| person.home.clone())
+            .find(|home| *map.get_tile(home) == Tile::Door(Location::Home, None));
+
+        moves.extend(home.map(PlayerCommand::Lockdown));
+
+        if world.time.days >= 1 && vaccine_centers.len() < 2 {
+            if let Some(busiest) = cluster(&everyone) {
+                moves.extend(
+                    empty_near(map, &busiest, CLUSTER_RADIUS, rng)
+                        .map(PlayerCommand::Vaccinecenter),
+                );
+            }
+        }
+
+        if let Some((position, _)) = map
+            .contaminated()
+            .filter(|(_, contamination)| contamination.load > 1.0)
+            .max_by(|(_, a), (_, b)| a.load.partial_cmp(&b.load).unwrap())
+        {
+            moves.push(PlayerCommand::DisinfectionCrew(position));
+        }
+
+        if let Some(hotspot) = &hotspot {
+            moves.extend(
+                empty_near(map, hotspot, CLUSTER_RADIUS, rng).map(PlayerCommand::MaskCampaign),
+            );
+            moves.extend(
+                empty_near(map, hotspot, CLUSTER_RADIUS, rng).map(PlayerCommand::HygieneCampaign),
+            );
+        }
+
+        if world.time.days >= 3
+            && !vaccine_centers.is_empty()
+            && observation.booster_campaign <= observation.tick
+        {
+            moves.push(PlayerCommand::BoosterCampaign);
+        }
+
+        moves
+    }
+
+    /// Moves for the virus, best first. The virus knows about every infection.
+    fn virus_moves(&mut self, observation: &Observation) -> Vec<PlayerCommand> {
+        let world = &observation.world;
+        let map = &world.map;
+        let rng = &mut self.rng;
+        let mut moves = Vec::new();
+        let test_centers = tiles(map, Tile::TestCenter);
+        let vaccine_centers = tiles(map, Tile::VaccineCenter);
+
+        let infected = world
+            .people
+            .iter()
+            .filter(|(id, person)| {
+                person.alive
+                    && person.infected()
+                    && !world
+                        .hospitals
+                        .values()
+                        .any(|hospital| hospital.patients.contains(id))
+            })
+            .collect::<Vec<_>>();
+
+        // the best spreaders are the infected that know the most people
+        let spreader = infected
+            .iter()
+            .max_by_key(|(_, person)| person.habits.acquaintances.len())
+            .map(|(id, _)| (*id).clone());
+
+        moves.extend(spreader.clone().map(PlayerCommand::PartyImpulse));
+
+        let cases = infected
+            .iter()
+            .map(|(_, person)| person.position.clone())
+            .collect::<Vec<_>>();
+
+        if let Some(hotspot) = cluster(&cases) {
+            moves.extend(
+                empty_near(map, &hotspot, CLUSTER_RADIUS, rng).map(PlayerCommand::SocialImpulse),
+            );
+        }
+
+        // the president relies on test and vaccine centers, so go after them
+        if test_centers.len() + vaccine_centers.len() >= 3 {
+            moves.push(PlayerCommand::EconomicCrash);
+        }
+
+        if let Some(center) = vaccine_centers.iter().choose(rng) {
+            moves.extend(
+                empty_near(map, center, CLUSTER_RADIUS, rng).map(PlayerCommand::AntivaxCampaign),
+            );
+        }
+
+        if let Some(center) = test_centers.iter().choose(rng) {
+            moves.extend(empty_near(map, center, 2, rng).map(PlayerCommand::Roadblock));
+        }
+
+        if let Some(spreader) = spreader {
+            let mutation = if vaccine_centers.is_empty() {
+                Mutation::Transmissibility
+            } else {
+                Mutation::VaccineEscape
+            };
+
+            moves.push(PlayerCommand::Mutate(spreader, mutation));
+        }
+
+        moves
+    }
+
+    /// Any move the side can make, with random targets.
+    fn random_moves(&mut self, observation: &Observation) -> Vec<PlayerCommand> {
+        let map = &observation.world.map;
+        let rng = &mut self.rng;
+        let center = Position::new(map.width / 2, map.height / 2);
+        let position = empty_near(map, &center, map.width.max(map.height), rng);
+        let person = observation.world.people.keys().choose(rng).cloned();
+
+        let command = match (self.side, position, person) {
+            (true, Some(position), Some(person)) => match rng.gen_range(0..4) {
+                0 => PlayerCommand::PartyImpulse(person),
+                1 => PlayerCommand::SocialImpulse(position),
+                2 => PlayerCommand::AntivaxCampaign(position),
+                _ => PlayerCommand::Roadblock(position),
+            },
+            (false, Some(position), _) => match rng.gen_range(0..4) {
+                0 => PlayerCommand::Testcenter(position),
+                1 => PlayerCommand::MaskCampaign(position),
+                2 => PlayerCommand::HygieneCampaign(position),
+                _ => PlayerCommand::Vaccinecenter(position),
+            },
+            _ => return Vec::new(),
+        };
+
+        vec![command]
+    }
+
+    /// Normal and hard bots research the upgrades of their side in order.
+    fn research(&self, observation: &Observation) -> Option<PlayerCommand> {
+        if self.difficulty == Difficulty::Easy {
+            return None;
+        }
+
+        let upgrades = &observation.upgrades;
+
+        Upgrade::ALL
+            .iter()
+            .filter(|upgrade| upgrade.side() == self.side && !upgrades.contains(upgrade))
+            .find(|upgrade| upgrade.unlocked(upgrades))
+            .map(|upgrade| PlayerCommand::Research(*upgrade))
+    }
+}
+
+impl Agent for Bot {
+    /// Decides on the next move, which is at most one command.
+    fn act(&mut self, observation: &Observation) -> Vec<PlayerCommand> {
+        let mut moves = if self.rng.gen_bool(self.difficulty.insight()) {
+            if self.side {
+                self.virus_moves(observation)
+            } else {
+                self.president_moves(observation)
+            }
+        } else {
+            self.random_moves(observation)
+        };
+
+        // the first day is spent getting started, after that research comes first
+        if let Some(research) = self.research(observation) {
+            if observation.world.time.days >= 1 {
+                moves.insert(0, research);
+            } else {
+                moves.push(research);
+            }
+        }
+
+        for command in moves {
+            if !observation.is_ready(command.kind()) {
+                continue;
+            }
+
+            // hard bots save up for their best move, the others take what they can afford
+            if observation.money < command.price_lookup(self.side, &observation.config.prices) {
+                if self.difficulty == Difficulty::Hard {
+                    return Vec::new();
+                }
+
+                continue;
+            }
+
+            return vec![command];
+        }
+
+        Vec::new()
+    }
+
+    fn interval(&self) -> u64 {
+        self.difficulty.think_ticks()
+    }
+}
+
+/// Positions of every tile of the *kind* on the map.
+fn tiles(map: &Map, kind: Tile) -> Vec<Position> {
+    (0..map.width)
+        .flat_map(|x| (0..map.height).map(move |y| Position::new(x, y)))
+        .filter(|position| *map.get_tile(position) == kind)
+        .collect()
+}
+
+/// Distance in steps, where diagonal steps count as one.
+fn distance(a: &Position, b: &Position) -> usize {
+    (a.x as i64 - b.x as i64)
+        .abs()
+        .max((a.y as i64 - b.y as i64).abs()) as usize
+}
+
+/// The position with the most others close to it.
+fn cluster(positions: &[Position]) -> Option<Position> {
+    let mut index = SpatialIndex::new(CLUSTER_RADIUS);
+
+    for (i, position) in positions.iter().enumerate() {
+        index.insert(position.clone(), PersonId(i as u32));
+    }
+
+    positions
+        .iter()
+        .max_by_key(|position| index.near(position, CLUSTER_RADIUS).count())
+        .cloned()
+}
+
+/// A random empty tile within *radius* of *position*, which is where campaigns and centers
+/// can be placed.
+fn empty_near(
+    map: &Map,
+    position: &Position,
+    radius: usize,
+    rng: &mut impl Rng,
+) -> Option<Position> {
+    let xs = position.x.saturating_sub(radius)..=(position.x + radius).min(map.width - 1);
+
+    xs.flat_map(|x| {
+        let ys = position.y.saturating_sub(radius)..=(position.y + radius).min(map.height - 1);
+        ys.map(move |y| Position::new(x, y))
+    })
+    .filter(|p| *map.get_tile(p) == Tile::Empty)
+    .choose(rng)
+}
@@ -0,0 +1,265 @@
+//! The camera decides which part of the map is shown, so maps can be bigger than the window.
+//! Every zoom level has a console of its own, with fewer and bigger characters than the one
+//! below, and the ui is drawn on a console above all of them.
+
+use crate::map::{Position, Tile};
+use crate::world::World;
+use bracket_lib::prelude::*;
+
+/// Width of the side panel in ui characters, the map is shown to the right of it.
+pub const PANEL_WIDTH: i32 = 30;
+/// Ui characters every tile is wide and tall at each zoom level.
+pub const ZOOM_LEVELS: [i32; 3] = [1, 2, 3];
+/// The console the ui is drawn on, the ones below it belong to the zoom levels.
+pub const UI_CONSOLE: usize = ZOOM_LEVELS.len();
+/// Size of the minimap in ui characters, it shrinks the map until it fits.
+const MINIMAP_WIDTH: i32 = 36;
+const MINIMAP_HEIGHT: i32 = 24;
+
+pub struct Camera {
+    /// Tile in the top left corner of the view
+    pub x: i32,
+    pub y: i32,
+    /// Index of the zoom level, which is also the console the map is drawn on
+    pub zoom: usize,
+    /// Where the mouse and camera were when dragging started
+    drag: Option<(Point, Point)>,
+    /// Shows the minimap while part of the map is out of view
+    pub minimap: bool,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            zoom: 0,
+            drag: None,
+            minimap: true,
+        }
+    }
+}
+
+impl Camera {
+    pub fn scale(&self) -> i32 {
+        ZOOM_LEVELS[self.zoom]
+    }
+
+    /// Offset to draw the map at on the console of the zoom level.
+    pub fn offset(&self) -> Point {
+        Point::new(PANEL_WIDTH / self.scale() - self.x, -self.y)
+    }
+
+    /// Tiles that fit in a view of *width* by *height* ui characters.
+    fn view(&self, width: i32, height: i32) -> (i32, i32) {
+        (width / self.scale(), height / self.scale())
+    }
+
+    /// The tile under a point on the ui console, if the map is shown there.
+    pub fn tile_at(
+        &self,
+        point: Point,
+        world: &World,
+        width: i32,
+        height: i32,
+    ) -> Option<Position> {
+        if point.x < PANEL_WIDTH || self.on_minimap(point, world, width, height) {
+            return None;
+        }
+
+        let x = (point.x - PANEL_WIDTH) / self.scale() + self.x;
+        let y = point.y / self.scale() + self.y;
+        let position = Position::new(x.max(0) as usize, y.max(0) as usize);
+
+        if x >= 0 && y >= 0 && world.map.in_bounds(&position) {
+            Some(position)
+        } else {
+            None
+        }
+    }
+
+    /// Pans with the arrow keys or by dragging with the right mouse button, and zooms with
+    /// plus and minus. The keys are only used when *keyboard* is set, so they can be typed
+    /// into the ui. The view is *width* by *height* ui characters.
+    pub fn update(&mut self, ctx: &BTerm, world: &World, keyboard: bool, width: i32, height: i32) {
+        let mouse = ctx.mouse_point();
+        let input = INPUT.lock();
+
+        if keyboard {
+            if input.is_key_pressed(VirtualKeyCode::Left) {
+                self.x -= 1;
+            }
+            if input.is_key_pressed(VirtualKeyCode::Right) {
+                self.x += 1;
+            }
+            if input.is_key_pressed(VirtualKeyCode::Up) {
+                self.y -= 1;
+            }
+            if input.is_key_pressed(VirtualKeyCode::Down) {
+                self.y += 1;
+            }
+
+            match ctx.key {
+                Some(VirtualKeyCode::Equals) | Some(VirtualKeyCode::Plus) => {
+                    self.set_zoom(self.zoom + 1, width, height)
+                }
+                Some(VirtualKeyCode::Minus) => {
+                    self.set_zoom(self.zoom.saturating_sub(1), width, height)
+                }
+                _ => {}
+            }
+        }
+
+        if input.is_mouse_button_pressed(1) {
+            let (start, camera) = *self.drag.get_or_insert((mouse, Point::new(self.x, self.y)));
+            let moved = start - mouse;
+
+            self.x = camera.x + moved.x / self.scale();
+            self.y = camera.y + moved.y / self.scale();
+        } else {
+            self.drag = None;
+        }
+
+        // clicking the minimap moves the view there
+        if ctx.left_click {
+            if let Some((position, step)) = self.minimap_position(world, width, height) {
+                if self.on_minimap(mouse, world, width, height) {
+                    let (view_width, view_height) = self.view(width, height);
+                    self.x = (mouse.x - position.x) * step - view_width / 2;
+                    self.y = (mouse.y - position.y) * step - view_height / 2;
+                }
+            }
+        }
+
+        self.clamp(world, width, height);
+    }
+
+    /// Changes the zoom level, keeping the tile in the middle of the view where it is.
+    pub fn set_zoom(&mut self, zoom: usize, width: i32, height: i32) {
+        let (view_width, view_height) = self.view(width, height);
+        let center = Point::new(self.x + view_width / 2, self.y + view_height / 2);
+
+        self.zoom = zoom.min(ZOOM_LEVELS.len() - 1);
+
+        let (view_width, view_height) = self.view(width, height);
+        self.x = center.x - view_width / 2;
+        self.y = center.y - view_height / 2;
+    }
+
+    /// Moves the view so *position* is in the middle of it, or as close as the edge of the
+    /// map allows.
+    pub fn center_on(&mut self, position: &Position, world: &World, width: i32, height: i32) {
+        let (view_width, view_height) = self.view(width, height);
+
+        self.x = position.x as i32 - view_width / 2;
+        self.y = position.y as i32 - view_height / 2;
+        self.clamp(world, width, height);
+    }
+
+    /// Keeps the view on the map.
+    fn clamp(&mut self, world: &World, width: i32, height: i32) {
+        let (view_width, view_height) = self.view(width, height);
+
+        self.x = self.x.min(world.map.width as i32 - view_width).max(0);
+        self.y = self.y.min(world.map.height as i32 - view_height).max(0);
+    }
+
+    /// Where the minimap goes on the ui console and the tiles every character of it covers,
+    /// there is no minimap while the whole map is in view or it is turned off.
+    fn minimap_position(&self, world: &World, width: i32, height: i32) -> Option<(Point, i32)> {
+        let (view_width, view_height) = self.view(width, height);
+        let (map_width, map_height) = (world.map.width as i32, world.map.height as i32);
+
+        if !self.minimap || (map_width <= view_width && map_height <= view_height) {
+            return None;
+        }
+
+        let step = ((map_width + MINIMAP_WIDTH - 1) / MINIMAP_WIDTH)
+            .max((map_height + MINIMAP_HEIGHT - 1) / MINIMAP_HEIGHT)
+            .max(1);
+        let position = Point::new(
+            PANEL_WIDTH + width - map_width / step - 1,
+            height - map_height / step - 1,
+        );
+
+        Some((position, step))
+    }
+
+    fn on_minimap(&self, point: Point, world: &World, width: i32, height: i32) -> bool {
+        match self.minimap_position(world, width, height) {
+            Some((position, step)) => {
+                point.x >= position.x
+                    && point.y >= position.y
+                    && point.x < position.x + world.map.width as i32 / step
+                    && point.y < position.y + world.map.height as i32 / step
+            }
+            None => false,
+        }
+    }
+
+    /// Draws the whole map shrunk down in the corner of the view, with buildings, known
+    /// cases and the part of the map that is in view.
+    pub fn render_minimap(
+        &self,
+        ctx: &mut BTerm,
+        world: &World,
+        side: bool,
+        width: i32,
+        height: i32,
+    ) {
+        let (position, step) = match self.minimap_position(world, width, height) {
+            Some(minimap) => minimap,
+            None => return,
+        };
+
+        let map = &world.map;
+        let (minimap_width, minimap_height) = (map.width as i32 / step, map.height as i32 / step);
+
+        ctx.draw_box(
+            position.x - 1,
+            position.y - 1,
+            minimap_width + 1,
+            minimap_height + 1,
+            GREEN,
+            BLACK,
+        );
+
+        for x in 0..minimap_width {
+            for y in 0..minimap_height {
+                let built = (0..step).any(|dx| {
+                    (0..step).any(|dy| {
+                        let tile =
+                            Position::new((x * step + dx) as usize, (y * step + dy) as usize);
+                        map.in_bounds(&tile) && !matches!(map.get_tile(&tile), Tile::Empty)
+                    })
+                });
+
+                if built {
+                    ctx.set_bg(position.x + x, position.y + y, (40, 40, 40));
+                }
+            }
+        }
+
+        for (id, person) in &world.people {
+            let point = Point::new(person.position.x, person.position.y) / step;
+
+            if person.alive
+                && point.x < minimap_width
+                && point.y < minimap_height
+                && world.visible_strain(id, side).is_some()
+            {
+                ctx.set_bg(position.x + point.x, position.y + point.y, DARK_RED);
+            }
+        }
+
+        let (view_width, view_height) = self.view(width, height);
+        ctx.draw_hollow_box(
+            position.x + self.x / step,
+            position.y + self.y / step,
+            (view_width / step).min(minimap_width - 1),
+            (view_height / step).min(minimap_height - 1),
+            YELLOW,
+            BLACK,
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::camera::{PANEL_WIDTH, ZOOM_LEVELS};
 use crate::server::{NetworkPayload, PlayerCommand};
 use crate::state;
 use bracket_lib::prelude::*;
@@ -68,14 +69,27 @@ async fn client_main(
 #[tokio::main]
 pub async fn run(ip: String) -> Result<(), Box<dyn std::error::Error + 'static + Send + Sync>> {
     // init termial
-    let ctx = BTermBuilder::simple(
-        crate::MAP_WIDTH_CHUNKS * 6 + 30,
-        crate::MAP_HEIGHT_CHUNKS * 6,
-    )?
-    .with_title("MBW")
-    .with_vsync(true)
-    .with_fps_cap(60.0)
-    .build()?;
+    let (width, height) = (
+        crate::MAP_WIDTH_CHUNKS as i32 * 6 + PANEL_WIDTH,
+        crate::MAP_HEIGHT_CHUNKS as i32 * 6,
+    );
+    let mut builder = BTermBuilder::new()
+        .with_dimensions(width, height)
+        .with_tile_dimensions(8, 8)
+        .with_font("terminal8x8.png", 8, 8);
+
+    // a console for the map at every zoom level, stretched over the window, and the ui on top
+    for zoom in ZOOM_LEVELS.iter() {
+        builder = builder.with_simple_console(width / zoom, height / zoom, "terminal8x8.png");
+    }
+
+    let ctx = builder
+        .with_simple_console(width, height, "terminal8x8.png")
+        .with_title("MBW")
+        .with_advanced_input(true)
+        .with_vsync(true)
+        .with_fps_cap(60.0)
+        .build()?;
 
     // Client network queue for server tick updates
     let (client_sender, client_receiver) = channel();
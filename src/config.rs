@@ -0,0 +1,783 @@
+use crate::person::JobType;
+use crate::world::Calendar;
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Amount of ticks in an in-game day, a tick being one in-game minute.
+pub const TICKS_PER_DAY: u64 = 1440;
+
+/// Converts an amount of in-game days to ticks.
+pub fn days_to_ticks(days: f32) -> u64 {
+    (days * TICKS_PER_DAY as f32) as u64
+}
+
+/// Parameters for the simulation that both the server and clients need to agree on.
+///
+/// The server can load them from a rules file, anything left out of the file keeps its
+/// default value.
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Config {
+    pub game: GameConfig,
+    pub win: WinConfig,
+    pub prices: PriceConfig,
+    pub cooldowns: CooldownConfig,
+    pub economy: EconomyConfig,
+    pub population: PopulationConfig,
+    pub immunity: ImmunityConfig,
+    pub testing: TestingConfig,
+    pub transmission: TransmissionConfig,
+    pub buildings: BuildingConfig,
+    pub opinion: OpinionConfig,
+    pub hygiene: HygieneConfig,
+    pub contamination: ContaminationConfig,
+    pub severity: SeverityConfig,
+    pub calendar: Calendar,
+}
+
+impl Config {
+    /// Reads a rules file written in RON.
+    pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error + 'static + Send + Sync>> {
+        let rules = std::fs::read_to_string(path)?;
+        let config: Self = ron::de::from_str(&rules)?;
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Checks the values the simulation can't run with, so a bad rules file is refused when
+    /// it is loaded rather than crashing the game once they are used.
+    pub fn validate(&self) -> Result<(), String> {
+        let population = &self.population;
+
+        if self.game.map_width == 0 || self.game.map_height == 0 {
+            return Err(format!(
+                "game: a map of {} by {} chunks has no tiles",
+                self.game.map_width, self.game.map_height
+            ));
+        }
+
+        shares(
+            "population.age_groups",
+            population.age_groups.iter().map(|group| group.share),
+        )?;
+        shares(
+            "population.job_shares",
+            population.job_shares.iter().map(|(_, share)| *share),
+        )?;
+
+        if let Some(group) = population
+            .age_groups
+            .iter()
+            .find(|group| group.min_age > group.max_age)
+        {
+            return Err(format!(
+                "population.age_groups: {} to {} is not a range of ages",
+                group.min_age, group.max_age
+            ));
+        }
+
+        if population.size == 0 && self.game.initial_infections > 0 {
+            return Err("population.size: there is nobody to infect at the start".to_string());
+        }
+
+        if self.severity.bands.is_empty() {
+            return Err("severity.bands: at least one age band is needed".to_string());
+        }
+
+        for band in &self.severity.bands {
+            probability("severity.bands: symptomatic", band.symptomatic)?;
+            probability("severity.bands: hospitalization", band.hospitalization)?;
+            probability("severity.bands: fatality", band.fatality)?;
+        }
+
+        probability("population.unemployment", population.unemployment)?;
+        probability("testing.sensitivity", self.testing.sensitivity)?;
+        probability("testing.specificity", self.testing.specificity)?;
+        probability("economy.crash_layoffs", self.economy.crash_layoffs)?;
+        probability("economy.rehire_chance", self.economy.rehire_chance)?;
+        probability("calendar.travel_chance", self.calendar.travel_chance)?;
+        probability("calendar.import_chance", self.calendar.import_chance)?;
+
+        Ok(())
+    }
+}
+
+/// Shares get picked from at random, which needs at least one of them to be above 0.
+fn shares(name: &str, shares: impl Iterator<Item = f32>) -> Result<(), String> {
+    let mut total = 0.0;
+
+    for share in shares {
+        if share < 0.0 || share.is_nan() {
+            return Err(format!("{}: the share {} is below 0", name, share));
+        }
+
+        total += share;
+    }
+
+    if total > 0.0 {
+        Ok(())
+    } else {
+        Err(format!("{}: at least one share has to be above 0", name))
+    }
+}
+
+fn probability(name: &str, value: f64) -> Result<(), String> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("{}: {} is not between 0 and 1", name, value))
+    }
+}
+
+/// How a game starts, plays out and ends.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    /// Amount of people infected with the original strain at the start.
+    pub initial_infections: u32,
+    /// Chance of an infection between two people in close contact, before any modifiers.
+    pub infection_chance: f32,
+    /// Days from infection until a person recovers.
+    pub recovery_days: f32,
+    /// The game ends after this many days.
+    pub length_days: u32,
+    /// Size of the map in chunks of 6 by 6 tiles, maps bigger than the window can be panned.
+    pub map_width: usize,
+    pub map_height: usize,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            initial_infections: 10,
+            infection_chance: 0.15,
+            recovery_days: 3.0,
+            length_days: 4,
+            map_width: crate::MAP_WIDTH_CHUNKS,
+            map_height: crate::MAP_HEIGHT_CHUNKS,
+        }
+    }
+}
+
+/// What either side has to achieve to win, either before or when the game runs out of time.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WinConfig {
+    /// The virus wins if this share of the people is infected when time runs out.
+    pub infection_share: f32,
+    /// The virus wins once this share of the people has died.
+    pub death_share: f32,
+    /// The president wins once nobody is infected anymore.
+    pub eradication: bool,
+}
+
+impl Default for WinConfig {
+    fn default() -> Self {
+        Self {
+            infection_share: 0.5,
+            death_share: 0.1,
+            eradication: true,
+        }
+    }
+}
+
+/// How the players earn their money from the simulation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EconomyConfig {
+    /// Money both players earn every tick regardless of the simulation.
+    pub base_income: f32,
+    /// Value a person at work produces every tick.
+    pub output_per_worker: f32,
+    /// Share of the output the president earns in taxes.
+    pub tax_rate: f32,
+    /// Money the president pays every tick for each person in lockdown.
+    pub lockdown_support: f32,
+    /// Money the virus earns for every new infection.
+    pub infection_bounty: f32,
+    /// Share of the employed that lose their job in an economic crash.
+    pub crash_layoffs: f64,
+    /// Chance of a person that lost their job getting it back each day.
+    pub rehire_chance: f64,
+}
+
+impl Default for EconomyConfig {
+    fn default() -> Self {
+        Self {
+            base_income: 0.25,
+            output_per_worker: 1.0,
+            tax_rate: 0.02,
+            lockdown_support: 0.05,
+            infection_bounty: 15.0,
+            crash_layoffs: 0.25,
+            rehire_chance: 0.2,
+        }
+    }
+}
+
+/// What every player command costs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PriceConfig {
+    pub party_impulse: u32,
+    pub antivax_campaign: u32,
+    /// Roadblocks cost the virus more than the president.
+    pub virus_roadblock: u32,
+    pub president_roadblock: u32,
+    pub social_impulse: u32,
+    pub economic_crash: u32,
+    pub testcenter: u32,
+    pub lockdown: u32,
+    pub vaccinecenter: u32,
+    pub mask_campaign: u32,
+    pub hygiene_campaign: u32,
+    pub disinfection_crew: u32,
+    pub mutate: u32,
+    pub booster_campaign: u32,
+    pub rapid_tests: u32,
+    pub mass_testing: u32,
+    pub stronger_masks: u32,
+    pub faster_rollout: u32,
+    pub lockdown_relief: u32,
+    pub airborne: u32,
+    pub asymptomatic: u32,
+    pub surface_survival: u32,
+    pub superspreading: u32,
+    pub soap_resistance: u32,
+}
+
+impl Default for PriceConfig {
+    fn default() -> Self {
+        Self {
+            party_impulse: 250,
+            antivax_campaign: 800,
+            virus_roadblock: 150,
+            president_roadblock: 80,
+            social_impulse: 180,
+            economic_crash: 800,
+            testcenter: 300,
+            lockdown: 100,
+            vaccinecenter: 600,
+            mask_campaign: 200,
+            hygiene_campaign: 200,
+            disinfection_crew: 150,
+            mutate: 500,
+            booster_campaign: 400,
+            rapid_tests: 600,
+            mass_testing: 1000,
+            stronger_masks: 800,
+            faster_rollout: 900,
+            lockdown_relief: 700,
+            airborne: 900,
+            asymptomatic: 800,
+            surface_survival: 600,
+            superspreading: 1200,
+            soap_resistance: 1000,
+        }
+    }
+}
+
+/// Hours a player has to wait before using a command again, counted from when it was last
+/// carried out.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CooldownConfig {
+    pub party_impulse: f32,
+    pub antivax_campaign: f32,
+    pub roadblock: f32,
+    pub social_impulse: f32,
+    pub economic_crash: f32,
+    pub testcenter: f32,
+    pub lockdown: f32,
+    pub vaccinecenter: f32,
+    pub mask_campaign: f32,
+    pub hygiene_campaign: f32,
+    pub disinfection_crew: f32,
+    pub mutate: f32,
+    pub booster_campaign: f32,
+}
+
+impl Default for CooldownConfig {
+    fn default() -> Self {
+        Self {
+            party_impulse: 6.0,
+            antivax_campaign: 12.0,
+            roadblock: 0.5,
+            social_impulse: 6.0,
+            economic_crash: 48.0,
+            testcenter: 2.0,
+            lockdown: 1.0,
+            vaccinecenter: 4.0,
+            mask_campaign: 3.0,
+            hygiene_campaign: 3.0,
+            disinfection_crew: 2.0,
+            mutate: 24.0,
+            booster_campaign: 48.0,
+        }
+    }
+}
+
+/// Share of the population in a range of ages.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AgeGroup {
+    pub min_age: u8,
+    pub max_age: u8,
+    pub share: f32,
+}
+
+impl AgeGroup {
+    fn new(min_age: u8, max_age: u8, share: f32) -> Self {
+        Self {
+            min_age,
+            max_age,
+            share,
+        }
+    }
+}
+
+/// Who lives in the city, their ages and what they do.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PopulationConfig {
+    /// Amount of people in the city.
+    pub size: u32,
+    /// The age pyramid, the shares don't have to add up to 1.
+    pub age_groups: Vec<AgeGroup>,
+    /// Children go to school from this age.
+    pub school_age: u8,
+    /// Adults start working from this age.
+    pub working_age: u8,
+    /// People stop working at this age.
+    pub retirement_age: u8,
+    /// Share of the working age population without a job.
+    pub unemployment: f64,
+    /// Share of the workforce in each job, the shares don't have to add up to 1.
+    pub job_shares: Vec<(JobType, f32)>,
+}
+
+impl Default for PopulationConfig {
+    fn default() -> Self {
+        Self {
+            size: 400,
+            age_groups: vec![
+                AgeGroup::new(0, 5, 0.065),
+                AgeGroup::new(6, 17, 0.135),
+                AgeGroup::new(18, 29, 0.16),
+                AgeGroup::new(30, 44, 0.19),
+                AgeGroup::new(45, 59, 0.2),
+                AgeGroup::new(60, 66, 0.08),
+                AgeGroup::new(67, 79, 0.13),
+                AgeGroup::new(80, 95, 0.04),
+            ],
+            school_age: 6,
+            working_age: 18,
+            retirement_age: 67,
+            unemployment: 0.06,
+            job_shares: vec![
+                (JobType::Doctor, 0.05),
+                (JobType::Programmer, 0.12),
+                (JobType::Clerk, 0.25),
+                (JobType::PoliceOfficer, 0.04),
+                (JobType::FireFighter, 0.02),
+                (JobType::PublicServant, 0.22),
+                (JobType::Chef, 0.15),
+                (JobType::Teacher, 0.15),
+            ],
+        }
+    }
+}
+
+impl PopulationConfig {
+    /// Picks an age from the age pyramid.
+    pub fn generate_age(&self, rng: &mut impl Rng) -> u8 {
+        let shares = self.age_groups.iter().map(|group| group.share);
+        let group = &self.age_groups[WeightedIndex::new(shares).unwrap().sample(rng)];
+
+        rng.gen_range(group.min_age..=group.max_age)
+    }
+
+    /// Picks what someone of *age* does during the day.
+    pub fn generate_job_type(&self, age: u8, rng: &mut impl Rng) -> JobType {
+        if age < self.school_age {
+            JobType::Child
+        } else if age < self.working_age {
+            JobType::Student
+        } else if age >= self.retirement_age {
+            JobType::Retired
+        } else if rng.gen_bool(self.unemployment) {
+            JobType::Unemployed
+        } else {
+            let shares = self.job_shares.iter().map(|(_, share)| *share);
+            let index = WeightedIndex::new(shares).unwrap().sample(rng);
+
+            self.job_shares[index].0.clone()
+        }
+    }
+}
+
+/// How protection from vaccines and past infections builds up and wanes.
+///
+/// Protection is the share of the infection chance that is removed, so `0.95` means a 95%
+/// lower chance of getting infected. It halves every *half life* days.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImmunityConfig {
+    /// Protection right after each dose of the primary course, the length of which is the
+    /// amount of doses in the course.
+    pub dose_efficacy: Vec<f32>,
+    /// Protection right after a booster dose.
+    pub booster_efficacy: f32,
+    /// Minimum amount of days between two doses of the primary course.
+    pub days_between_doses: f32,
+    /// Minimum amount of days after the last dose before a booster is given.
+    pub days_before_booster: f32,
+    /// Amount of days a booster campaign lasts.
+    pub booster_campaign_days: f32,
+    pub vaccine_half_life_days: f32,
+    /// Protection right after recovering from an infection.
+    pub infection_efficacy: f32,
+    pub infection_half_life_days: f32,
+}
+
+impl Default for ImmunityConfig {
+    fn default() -> Self {
+        Self {
+            dose_efficacy: vec![0.6, 0.95],
+            booster_efficacy: 0.95,
+            days_between_doses: 1.0,
+            days_before_booster: 2.0,
+            booster_campaign_days: 1.0,
+            vaccine_half_life_days: 4.0,
+            infection_efficacy: 0.9,
+            infection_half_life_days: 3.0,
+        }
+    }
+}
+
+impl ImmunityConfig {
+    pub fn primary_doses(&self) -> u8 {
+        self.dose_efficacy.len() as u8
+    }
+}
+
+/// How accurate, fast and available tests are.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TestingConfig {
+    /// Chance of an infected person testing positive.
+    pub sensitivity: f64,
+    /// Chance of a healthy person testing negative.
+    pub specificity: f64,
+    /// Amount of days between a sample being analysed and the result coming back.
+    pub result_delay_days: f32,
+    /// Amount of samples a single test center can analyse each day.
+    pub daily_capacity: u32,
+    /// Amount of days a result is valid, after which the person can get tested again.
+    pub result_valid_days: f32,
+}
+
+impl Default for TestingConfig {
+    fn default() -> Self {
+        Self {
+            sensitivity: 0.85,
+            specificity: 0.97,
+            result_delay_days: 0.25,
+            daily_capacity: 40,
+            result_valid_days: 2.0,
+        }
+    }
+}
+
+/// How close people have to be, for how long, and where, to infect each other.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransmissionConfig {
+    /// Maximum distance in tiles over which people can infect each other.
+    pub contact_radius: usize,
+    /// Multiplier on the infection chance for every tile between two people.
+    pub distance_falloff: f32,
+    /// Multiplier on the infection chance for people inside buildings.
+    pub indoor_multiplier: f32,
+    /// Multiplier on the infection chance for people outside.
+    pub outdoor_multiplier: f32,
+    /// Amount of ticks two people have to be close before the full infection chance applies,
+    /// and before they have a chance of becoming acquainted.
+    pub exposure_ticks: u32,
+}
+
+impl Default for TransmissionConfig {
+    fn default() -> Self {
+        Self {
+            contact_radius: 2,
+            distance_falloff: 0.5,
+            indoor_multiplier: 2.0,
+            outdoor_multiplier: 0.5,
+            exposure_ticks: 15,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BuildingConfig {
+    /// Amount of people that fit in each room tile of a building. Once every room is full,
+    /// newcomers wait outside the door.
+    pub people_per_room: usize,
+}
+
+impl Default for BuildingConfig {
+    fn default() -> Self {
+        Self { people_per_room: 2 }
+    }
+}
+
+/// How attitudes towards masks, hygiene and vaccines change over time.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpinionConfig {
+    /// Share of the difference to the average opinion of their acquaintances a person takes
+    /// over every hour.
+    pub influence: f32,
+    /// Maximum distance in tiles from a campaign at which people are reached by it.
+    pub campaign_radius: usize,
+    /// Change in attitude every tick for people reached by a campaign.
+    pub campaign_strength: f32,
+    /// How long a campaign runs, in days.
+    pub campaign_days: f32,
+    /// Change in attitude towards following the measures when an acquaintance dies.
+    pub grief: f32,
+    /// Change in attitude against following the measures when a household is locked down.
+    pub lockdown_resentment: f32,
+}
+
+impl Default for OpinionConfig {
+    fn default() -> Self {
+        Self {
+            influence: 0.05,
+            campaign_radius: 4,
+            campaign_strength: 0.01,
+            campaign_days: 1.0 / 3.0,
+            grief: 0.15,
+            lockdown_resentment: 0.1,
+        }
+    }
+}
+
+/// How washing hands keeps the virus from spreading through touch and shared surfaces.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HygieneConfig {
+    /// Share of the infection chance removed for someone who always washes their hands,
+    /// both when passing the virus on and when picking it up.
+    pub handwashing: f32,
+    /// Change in hygiene for people getting a test result, as they are reminded of the virus.
+    pub testing_boost: f32,
+}
+
+impl Default for HygieneConfig {
+    fn default() -> Self {
+        Self {
+            handwashing: 0.4,
+            testing_boost: 0.1,
+        }
+    }
+}
+
+/// How infected people contaminate the tiles they are on, and how others get infected from
+/// touching them.
+///
+/// The surfaces of public toilets and shops are shared by everyone inside, and get touched
+/// a lot more than anywhere else.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContaminationConfig {
+    /// Viral load an infected person leaves every tick.
+    pub shedding: f32,
+    /// Multiplier on the viral load left in public toilets and shops, which is spread over
+    /// the whole building.
+    pub hotspot_multiplier: f32,
+    /// Amount of hours it takes for the viral load on a tile to halve.
+    pub half_life_hours: f32,
+    /// Chance, in percent, of getting infected every tick for each unit of viral load on the
+    /// tile.
+    pub infectivity: f32,
+    /// Distance in tiles around a disinfection crew that gets cleaned.
+    pub disinfection_radius: usize,
+}
+
+impl Default for ContaminationConfig {
+    fn default() -> Self {
+        Self {
+            shedding: 0.05,
+            hotspot_multiplier: 5.0,
+            half_life_hours: 2.0,
+            infectivity: 0.01,
+            disinfection_radius: 6,
+        }
+    }
+}
+
+/// Rates for a range of ages, where each rate is the chance of it happening for every
+/// infection.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AgeBand {
+    /// Oldest age in the band, the band starts after the previous one.
+    pub max_age: u8,
+    /// Multiplier on the chance of getting infected.
+    pub susceptibility: f32,
+    /// Chance of showing symptoms, which keeps people at home.
+    pub symptomatic: f64,
+    /// Chance of becoming severely ill and needing a hospital bed.
+    pub hospitalization: f64,
+    /// Chance of dying once the infection is over.
+    pub fatality: f64,
+}
+
+impl AgeBand {
+    fn new(
+        max_age: u8,
+        susceptibility: f32,
+        symptomatic: f64,
+        hospitalization: f64,
+        fatality: f64,
+    ) -> Self {
+        Self {
+            max_age,
+            susceptibility,
+            symptomatic,
+            hospitalization,
+            fatality,
+        }
+    }
+}
+
+/// How the outcome of an infection depends on age and health.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SeverityConfig {
+    /// Sorted by age, ages past the last band use the last band.
+    pub bands: Vec<AgeBand>,
+    /// Multiplier on the hospitalization and fatality rates for every comorbidity a person has.
+    pub comorbidity_multiplier: f64,
+    /// Multiplier on the fatality rate for severe cases.
+    pub severe_multiplier: f64,
+    /// Additional multiplier on the fatality rate for severe cases that never got a bed.
+    pub untreated_multiplier: f64,
+}
+
+impl Default for SeverityConfig {
+    fn default() -> Self {
+        Self {
+            bands: vec![
+                AgeBand::new(9, 0.5, 0.2, 0.005, 0.0005),
+                AgeBand::new(19, 0.8, 0.3, 0.01, 0.001),
+                AgeBand::new(29, 1.0, 0.4, 0.03, 0.005),
+                AgeBand::new(39, 1.1, 0.45, 0.05, 0.01),
+                AgeBand::new(49, 1.2, 0.5, 0.08, 0.03),
+                AgeBand::new(59, 1.3, 0.55, 0.12, 0.06),
+                AgeBand::new(69, 1.4, 0.6, 0.2, 0.12),
+                AgeBand::new(79, 1.6, 0.7, 0.3, 0.2),
+                AgeBand::new(u8::MAX, 1.8, 0.75, 0.4, 0.3),
+            ],
+            comorbidity_multiplier: 1.8,
+            severe_multiplier: 2.0,
+            untreated_multiplier: 3.0,
+        }
+    }
+}
+
+impl SeverityConfig {
+    pub fn band(&self, age: u8) -> &AgeBand {
+        self.bands
+            .iter()
+            .find(|band| age <= band.max_age)
+            .unwrap_or_else(|| self.bands.last().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_files_load() {
+        for rules in &["default.ron", "outbreak.ron"] {
+            let path = format!("{}/rules/{}", env!("CARGO_MANIFEST_DIR"), rules);
+
+            if let Err(error) = Config::load(&path) {
+                panic!("{}: {}", rules, error);
+            }
+        }
+    }
+
+    #[test]
+    fn default_rules_match_the_defaults() {
+        let path = format!("{}/rules/default.ron", env!("CARGO_MANIFEST_DIR"));
+        let rules = Config::load(&path).unwrap();
+
+        assert_eq!(
+            ron::ser::to_string(&rules).unwrap(),
+            ron::ser::to_string(&Config::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        assert_eq!(Config::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn invalid_values_are_refused() {
+        let mut config = Config::default();
+        config
+            .population
+            .job_shares
+            .iter_mut()
+            .for_each(|(_, share)| *share = 0.0);
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.population.age_groups[0].share = -1.0;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.testing.sensitivity = 1.5;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.economy.rehire_chance = -0.1;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.game.map_width = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.game.map_height = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.population.size = 0;
+        assert!(config.validate().is_err());
+        config.game.initial_infections = 0;
+        assert_eq!(config.validate(), Ok(()));
+
+        let mut config = Config::default();
+        config.severity.bands.clear();
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.severity.bands[0].symptomatic = -0.1;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.severity.bands[1].hospitalization = -0.1;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.severity.bands[2].fatality = -0.1;
+        assert!(config.validate().is_err());
+    }
+}
@@ -1,12 +1,22 @@
+mod agent;
+mod bot;
+mod camera;
 mod client;
+mod config;
 mod map;
 mod map_generation;
 mod names;
+mod overlay;
 mod person;
+mod research;
+mod schedule;
+mod score;
 mod server;
+mod spatial;
 mod state;
 mod structures;
 mod ui;
+mod virus;
 mod world;
 
 use clap::Clap;
@@ -21,13 +31,39 @@ pub struct Settings {
     server: bool,
     #[clap(short, long, default_value = "mbwgame.ddns.net:35566")]
     ip: String,
+    /// Rules file for the games hosted by the server
+    #[clap(short, long)]
+    rules: Option<String>,
+    /// Bots taking part in every game, with one bot a player can play alone and two bots
+    /// play each other without anyone connecting
+    #[clap(short, long, default_value = "0")]
+    bots: u8,
+    /// How well the bots play: easy, normal or hard
+    #[clap(short, long, default_value = "normal")]
+    difficulty: bot::Difficulty,
+    /// Command starting an external agent to take part in every game before any bots, can
+    /// be given twice to have two agents play each other
+    #[clap(short, long)]
+    agent: Vec<String>,
+    /// Games to play in a row when nobody has to connect, after which the wins of each side
+    /// are counted up
+    #[clap(short, long, default_value = "1")]
+    games: u32,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error + 'static + Send + Sync>> {
     let settings = Settings::parse();
 
     if settings.server {
-        server::run(settings.ip)?;
+        let difficulty = settings.difficulty;
+        let seats = settings
+            .agent
+            .into_iter()
+            .map(agent::Seat::External)
+            .chain((0..settings.bots).map(|_| agent::Seat::Bot(difficulty)))
+            .collect();
+
+        server::run(settings.ip, settings.rules, seats, settings.games)?;
     } else {
         client::run(settings.ip)?;
     }
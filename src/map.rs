@@ -1,6 +1,8 @@
+use crate::virus::StrainId;
 use crate::world::Location;
 use bracket_lib::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
 pub struct Position {
@@ -14,9 +16,34 @@ impl Position {
     }
 }
 
+/// Serializes maps keyed by positions as a list of key and value pairs, as formats like JSON
+/// only allow strings as keys.
+pub mod by_position {
+    use super::Position;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    pub fn serialize<V: Serialize, S: Serializer>(
+        map: &HashMap<Position, V>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(map.iter())
+    }
+
+    pub fn deserialize<'de, V: Deserialize<'de>, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<Position, V>, D::Error> {
+        Ok(Vec::<(Position, V)>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub enum Tile {
     Empty,
+    /// Inside of a building
+    Floor,
     Building((u8, u8, u8)),
     Door(Location, Option<u32>),
     RoadBlock,
@@ -24,12 +51,37 @@ pub enum Tile {
     VaccineCenter,
     MaskCampain(u32),
     AntivaxCampain(u32),
+    HygieneCampain(u32),
+    /// Public toilet along the street
+    Toilet,
 }
 
 impl Tile {
+    /// People standing on indoor tiles share air, and infect each other more easily.
+    pub fn is_indoors(&self) -> bool {
+        matches!(self, Tile::Door(_, _) | Tile::Floor)
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Tile::Empty => "Street",
+            Tile::Floor => "Floor",
+            Tile::Building(_) => "Wall",
+            Tile::Door(_, _) => "Door",
+            Tile::RoadBlock => "Roadblock",
+            Tile::TestCenter => "Test center",
+            Tile::VaccineCenter => "Vaccine center",
+            Tile::MaskCampain(_) => "Mask campaign",
+            Tile::AntivaxCampain(_) => "Antivax campaign",
+            Tile::HygieneCampain(_) => "Hygiene campaign",
+            Tile::Toilet => "Public toilet",
+        }
+    }
+
     pub fn render(&self, point: &Point, ctx: &mut BTerm) {
         match self {
             Tile::Empty => {}
+            Tile::Floor => ctx.print_color(point.x, point.y, DIM_GRAY, BLACK, "."),
             Tile::Building(color) => ctx.print_color(point.x, point.y, *color, BLACK, "#"),
             Tile::Door(_, lockdown) => ctx.print_color(point.x, point.y, BURLYWOOD, BLACK, "["),
             Tile::RoadBlock => ctx.print_color(point.x, point.y, GRAY, BLACK, "X"),
@@ -37,15 +89,29 @@ impl Tile {
             Tile::VaccineCenter => ctx.print_color(point.x, point.y, GREEN, BLACK, "V"),
             Tile::MaskCampain(_) => ctx.print_color(point.x, point.y, GOLD, BLACK, "M"),
             Tile::AntivaxCampain(_) => ctx.print_color(point.x, point.y, RED, BLACK, "A"),
+            Tile::HygieneCampain(_) => ctx.print_color(point.x, point.y, AQUAMARINE, BLACK, "H"),
+            Tile::Toilet => ctx.print_color(point.x, point.y, LIGHT_CYAN, BLACK, "W"),
         }
     }
 }
 
+/// Viral load left on a tile by infected people.
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+pub struct Contamination {
+    pub load: f32,
+    /// The strain last left on the tile
+    pub strain: StrainId,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Map {
     pub width: usize,
     pub height: usize,
     pub tiles: Vec<Vec<Tile>>,
+    /// The [`Tile::Floor`] cells inside each building, by the position of its door.
+    #[serde(with = "by_position")]
+    pub rooms: HashMap<Position, Vec<Position>>,
+    pub contamination: Vec<Vec<Contamination>>,
 }
 
 impl Map {
@@ -54,6 +120,8 @@ impl Map {
             width,
             height,
             tiles: vec![vec![tile; height]; width],
+            rooms: HashMap::new(),
+            contamination: vec![vec![Contamination::default(); height]; width],
         }
     }
 
@@ -70,17 +138,62 @@ impl Map {
         }
     }
 
+    /// Gets every tile with enough viral load to matter.
+    pub fn contaminated(&self) -> impl Iterator<Item = (Position, Contamination)> + '_ {
+        self.contamination
+            .iter()
+            .enumerate()
+            .flat_map(|(x, column)| {
+                column
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, cell)| cell.load > 0.1)
+                    .map(move |(y, cell)| (Position::new(x, y), cell.clone()))
+            })
+    }
+
+    /// Colors the background of contaminated tiles, brighter the more viral load there is.
+    pub fn render_contamination(&self, ctx: &mut BTerm, offset: Point) {
+        for x in 0..self.width {
+            for y in 0..self.height {
+                let load = self.contamination[x][y].load;
+
+                if load > 0.1 {
+                    let point = Point::new(x, y) + offset;
+                    let color =
+                        RGB::named(BLACK).lerp(RGB::named(ORANGE_RED), (load / 5.0).min(1.0));
+                    ctx.set_bg(point.x, point.y, color);
+                }
+            }
+        }
+    }
+
     pub fn in_bounds(&self, position: &Position) -> bool {
         position.x < self.width && position.y < self.height
     }
 
+    /// The door of the building a tile belongs to, if it is a door or inside a building.
+    pub fn building_at(&self, position: &Position) -> Option<Position> {
+        match self.get_tile(position) {
+            Tile::Door(_, _) => Some(position.clone()),
+            Tile::Floor => self
+                .rooms
+                .iter()
+                .find(|(_, floor)| floor.contains(position))
+                .map(|(door, _)| door.clone()),
+            _ => None,
+        }
+    }
+
     pub fn can_walk(&self, position: &Position) -> bool {
         if self.in_bounds(position) {
             match self.tiles[position.x][position.y] {
                 Tile::Empty => true,
+                Tile::Floor => true,
                 Tile::Door(_, lockdown) => lockdown.is_none(),
                 Tile::TestCenter => true,
                 Tile::VaccineCenter => true,
+                Tile::Toilet => true,
                 _ => false,
             }
         } else {
@@ -2,10 +2,13 @@
 //! * Devide map into [`Chunk`]s.
 //! * Place [`Structure`]s randomly until no more can be placed.
 //! * Generate tiles from those structures.
+//! * Turn the empty tiles enclosed by each structure into [`Tile::Floor`] rooms.
+//! * Place public toilets along the streets.
 
-use crate::map::{self, Tile};
+use crate::map::{self, Position, Tile};
 use crate::world::Location;
 use rand::prelude::*;
+use std::collections::HashSet;
 
 #[derive(Clone, Debug)]
 pub struct Chunk {
@@ -35,6 +38,8 @@ pub struct MapGenerationSettings<'a> {
     pub width: usize,
     pub height: usize,
     pub structures: &'a [Structure],
+    /// Amount of public toilets to place on the streets.
+    pub toilets: usize,
 }
 
 impl<'a> MapGenerationSettings<'a> {
@@ -42,6 +47,7 @@ impl<'a> MapGenerationSettings<'a> {
     pub fn generate(&self, rng: &mut impl Rng) -> map::Map {
         let mut chunks: Vec<Vec<Option<(&Chunk, Location)>>> =
             vec![vec![None; self.height]; self.width];
+        let mut placed: Vec<(usize, usize, &Structure)> = Vec::new();
 
         // because hardcoding is okay if behind atleast 2 levels of indirection
         const MAX_TRIES: u32 = 10;
@@ -71,6 +77,8 @@ impl<'a> MapGenerationSettings<'a> {
                     chunks[x + p.x][y + p.y] = Some((chunk, location.clone()));
                 }
 
+                placed.push((x, y, structure));
+
                 times_tried = 0;
             } else {
                 times_tried += 1;
@@ -104,6 +112,82 @@ impl<'a> MapGenerationSettings<'a> {
             }
         }
 
+        for (x, y, structure) in placed {
+            let area = structure
+                .chunks
+                .iter()
+                .flat_map(|(p, _)| {
+                    (0..36)
+                        .map(move |i| Position::new((x + p.x) * 6 + i / 6, (y + p.y) * 6 + i % 6))
+                })
+                .collect::<HashSet<_>>();
+
+            let doors = area
+                .iter()
+                .filter(|p| matches!(map.get_tile(p), Tile::Door(_, _)))
+                .cloned()
+                .collect::<Vec<_>>();
+
+            if !doors.is_empty() {
+                let rooms = Self::interior(&map, &area);
+
+                for room in &rooms {
+                    map.tiles[room.x][room.y] = Tile::Floor;
+                }
+
+                // every door of the structure opens onto the same floor
+                for door in doors {
+                    map.rooms.insert(door, rooms.clone());
+                }
+            }
+        }
+
+        let streets = (0..map.width)
+            .flat_map(|x| (0..map.height).map(move |y| Position::new(x, y)))
+            .filter(|p| *map.get_tile(p) == Tile::Empty)
+            .collect::<Vec<_>>();
+
+        for toilet in streets.choose_multiple(rng, self.toilets) {
+            map.tiles[toilet.x][toilet.y] = Tile::Toilet;
+        }
+
         map
     }
+
+    /// Finds the empty tiles in *area* that are enclosed by walls, by flooding the empty
+    /// tiles from the edge of the area, everything not reached is inside.
+    fn interior(map: &map::Map, area: &HashSet<Position>) -> Vec<Position> {
+        let neighbors = |p: &Position| {
+            vec![
+                Position::new(p.x, p.y.wrapping_sub(1)),
+                Position::new(p.x, p.y + 1),
+                Position::new(p.x + 1, p.y),
+                Position::new(p.x.wrapping_sub(1), p.y),
+            ]
+        };
+
+        let mut outside = area
+            .iter()
+            .filter(|p| *map.get_tile(p) == Tile::Empty)
+            .filter(|p| neighbors(p).iter().any(|n| !area.contains(n)))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut visited = outside.iter().cloned().collect::<HashSet<_>>();
+
+        while let Some(p) = outside.pop() {
+            for n in neighbors(&p) {
+                if area.contains(&n)
+                    && *map.get_tile(&n) == Tile::Empty
+                    && visited.insert(n.clone())
+                {
+                    outside.push(n);
+                }
+            }
+        }
+
+        area.iter()
+            .filter(|p| *map.get_tile(p) == Tile::Empty && !visited.contains(p))
+            .cloned()
+            .collect()
+    }
 }
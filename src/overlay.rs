@@ -0,0 +1,267 @@
+//! Overlays color the background of the map with what the players need to decide where to
+//! act. Contacts and footfall aren't sent by the server, so the client keeps count of them
+//! itself from where people are every tick.
+
+use crate::map::{Map, Position, Tile};
+use crate::person::PersonId;
+use crate::spatial::SpatialIndex;
+use crate::world::World;
+use bracket_lib::prelude::*;
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Overlay {
+    None,
+    /// Viral load left on surfaces
+    Contamination,
+    /// People close to each tile right now
+    Density,
+    /// Contacts between people on each tile over the last day
+    Contacts,
+    /// Known infections close to each tile right now
+    Infections,
+    /// Share of the people living or working in each building that is vaccinated
+    Vaccination,
+    /// People walking over each tile outside over the last day
+    Footfall,
+}
+
+impl Overlay {
+    pub const ALL: [Overlay; 7] = [
+        Overlay::None,
+        Overlay::Contamination,
+        Overlay::Density,
+        Overlay::Contacts,
+        Overlay::Infections,
+        Overlay::Vaccination,
+        Overlay::Footfall,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Overlay::None => "Off",
+            Overlay::Contamination => "Contamination",
+            Overlay::Density => "Density",
+            Overlay::Contacts => "Contacts",
+            Overlay::Infections => "Known infections",
+            Overlay::Vaccination => "Vaccination",
+            Overlay::Footfall => "Footfall",
+        }
+    }
+
+    /// The overlay after this one, clicking the overlay button cycles through them.
+    pub fn next(&self) -> Overlay {
+        let index = Self::ALL.iter().position(|o| o == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn render(
+        &self,
+        ctx: &mut BTerm,
+        world: &World,
+        person_locations: &HashMap<Position, Vec<PersonId>>,
+        traffic: &Traffic,
+        side: bool,
+        offset: Point,
+    ) {
+        let map = &world.map;
+
+        match self {
+            Overlay::None => {}
+            Overlay::Contamination => map.render_contamination(ctx, offset),
+            Overlay::Density => {
+                let counts = person_locations
+                    .iter()
+                    .map(|(position, people)| (position.clone(), people.len() as u32))
+                    .collect();
+
+                render_heat(ctx, map, &spread(&counts), YELLOW, offset);
+            }
+            Overlay::Contacts => render_heat(ctx, map, &traffic.contacts.totals(), MAGENTA, offset),
+            Overlay::Infections => {
+                let counts = person_locations
+                    .iter()
+                    .map(|(position, people)| {
+                        let known = people
+                            .iter()
+                            .filter(|id| world.visible_strain(id, side).is_some())
+                            .count();
+
+                        (position.clone(), known as u32)
+                    })
+                    .collect();
+
+                render_heat(ctx, map, &spread(&counts), RED, offset);
+            }
+            Overlay::Vaccination => {
+                for (door, (vaccinated, people)) in coverage(world) {
+                    let share = vaccinated as f32 / people as f32;
+                    let color = RGB::named(RED).lerp(RGB::named(GREEN), share) * 0.6;
+
+                    for tile in map
+                        .rooms
+                        .get(&door)
+                        .into_iter()
+                        .flatten()
+                        .chain(Some(&door))
+                    {
+                        let point = Point::new(tile.x, tile.y) + offset;
+                        ctx.set_bg(point.x, point.y, color);
+                    }
+                }
+            }
+            Overlay::Footfall => {
+                let mut totals = traffic.footfall.totals();
+                totals.retain(|position, _| *map.get_tile(position) == Tile::Empty);
+
+                render_heat(ctx, map, &totals, CYAN, offset);
+            }
+        }
+    }
+}
+
+/// Counts per tile for the last day, kept an hour at a time so the oldest hour can be
+/// dropped once it is a day old.
+#[derive(Default)]
+pub struct DayCounter {
+    hours: VecDeque<HashMap<Position, u32>>,
+}
+
+impl DayCounter {
+    pub fn add(&mut self, position: &Position, count: u32) {
+        if self.hours.is_empty() {
+            self.next_hour();
+        }
+
+        *self
+            .hours
+            .back_mut()
+            .unwrap()
+            .entry(position.clone())
+            .or_default() += count;
+    }
+
+    /// Starts counting a new hour, forgetting the one from a day ago.
+    pub fn next_hour(&mut self) {
+        self.hours.push_back(HashMap::new());
+
+        if self.hours.len() > 24 {
+            self.hours.pop_front();
+        }
+    }
+
+    pub fn totals(&self) -> HashMap<Position, u32> {
+        let mut totals = HashMap::new();
+
+        for hour in &self.hours {
+            for (position, count) in hour {
+                *totals.entry(position.clone()).or_default() += count;
+            }
+        }
+
+        totals
+    }
+}
+
+/// Where people meet and walk, counted by the client from the positions it is sent.
+#[derive(Default)]
+pub struct Traffic {
+    pub contacts: DayCounter,
+    pub footfall: DayCounter,
+    /// Where everyone was the last tick
+    positions: HashMap<PersonId, Position>,
+    /// The last tick that was counted
+    tick: u64,
+}
+
+impl Traffic {
+    /// Counts the contacts and steps of everyone, once every tick.
+    pub fn update(&mut self, world: &World, radius: usize, tick: u64) {
+        if tick <= self.tick {
+            return;
+        }
+
+        if world.time.minutes == 0 {
+            self.contacts.next_hour();
+            self.footfall.next_hour();
+        }
+
+        self.tick = tick;
+
+        let mut index = SpatialIndex::new(radius.max(1));
+
+        for (id, person) in world.people.iter().filter(|(_, p)| p.alive) {
+            index.insert(person.position.clone(), id.clone());
+        }
+
+        for (id, person) in world.people.iter().filter(|(_, p)| p.alive) {
+            let others = index.near(&person.position, radius).count() as u32 - 1;
+
+            if others > 0 {
+                self.contacts.add(&person.position, others);
+            }
+
+            let moved = self.positions.get(id) != Some(&person.position);
+
+            if moved {
+                self.footfall.add(&person.position, 1);
+                self.positions.insert(id.clone(), person.position.clone());
+            }
+        }
+    }
+}
+
+/// Spreads the counts on each tile over the tiles around it, so crowds show up as areas
+/// instead of single tiles.
+fn spread(counts: &HashMap<Position, u32>) -> HashMap<Position, u32> {
+    let mut spread = HashMap::new();
+
+    for (position, count) in counts.iter().filter(|(_, count)| **count > 0) {
+        for x in position.x.saturating_sub(1)..=position.x + 1 {
+            for y in position.y.saturating_sub(1)..=position.y + 1 {
+                *spread.entry(Position::new(x, y)).or_default() += count;
+            }
+        }
+    }
+
+    spread
+}
+
+/// Vaccinated people and everyone living or working in each building, by its door.
+fn coverage(world: &World) -> HashMap<Position, (u32, u32)> {
+    let mut coverage: HashMap<Position, (u32, u32)> = HashMap::new();
+
+    for person in world.people.values().filter(|p| p.alive) {
+        let vaccinated = person.immunity.doses > 0;
+
+        for door in Some(&person.home).into_iter().chain(&person.job.location) {
+            let (count, people) = coverage.entry(door.clone()).or_default();
+            *count += vaccinated as u32;
+            *people += 1;
+        }
+    }
+
+    coverage
+}
+
+/// Colors the background of every tile by its count, brighter the higher it is compared to
+/// the highest count on the map.
+fn render_heat(
+    ctx: &mut BTerm,
+    map: &Map,
+    counts: &HashMap<Position, u32>,
+    color: (u8, u8, u8),
+    offset: Point,
+) {
+    let max = counts.values().copied().max().unwrap_or(0).max(1) as f32;
+
+    for (position, count) in counts
+        .iter()
+        .filter(|(position, count)| **count > 0 && map.in_bounds(position))
+    {
+        let point = Point::new(position.x, position.y) + offset;
+        let heat = RGB::named(BLACK).lerp(RGB::named(color), 0.2 + 0.8 * *count as f32 / max);
+
+        ctx.set_bg(point.x, point.y, heat);
+    }
+}
@@ -1,12 +1,14 @@
+use crate::config::{days_to_ticks, ImmunityConfig, SeverityConfig};
 use crate::map::{Map, Position};
 use crate::names::{FIRST_NAMES, LAST_NAMES};
+use crate::schedule::{Activity, Schedule};
 use crate::server::{GameSession, PathCache};
-use crate::world::{Time, World};
+use crate::virus::StrainId;
+use crate::world::{Calendar, Weekday, World};
 use bracket_lib::prelude::*;
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
-use std::ops::Range;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum JobType {
@@ -19,6 +21,10 @@ pub enum JobType {
     Chef,
     Teacher,
     Student,
+    /// Too young for school
+    Child,
+    Retired,
+    Unemployed,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -27,27 +33,82 @@ pub struct Job {
     pub location: Option<Position>,
 }
 
+/// The days of the week a shift is worked on.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WorkDays {
+    /// Every day, including holidays
+    EveryDay,
+    /// Monday to friday, except holidays
+    Weekdays,
+    /// Monday to saturday, except holidays
+    ExceptSunday,
+    /// Weekdays outside of the holidays and school holidays
+    SchoolDays,
+}
+
+/// Hours worked on the given days. Night shifts end the day after they start, so their
+/// end hour is before the start hour.
+#[derive(Clone, Debug)]
+pub struct Shift {
+    pub start: u32,
+    pub end: u32,
+    pub days: WorkDays,
+}
+
+impl Shift {
+    pub fn new(start: u32, end: u32, days: WorkDays) -> Self {
+        Self { start, end, days }
+    }
+
+    /// Checks if the shift starts on *day*.
+    pub fn works_on(&self, day: u32, calendar: &Calendar) -> bool {
+        match self.days {
+            WorkDays::EveryDay => true,
+            WorkDays::Weekdays => !calendar.is_day_off(day),
+            WorkDays::SchoolDays => !calendar.is_school_day_off(day),
+            WorkDays::ExceptSunday => {
+                Weekday::from_day(day) != Weekday::Sunday && !calendar.is_holiday(day)
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum PersonUpdate {
     LifeStatus(PersonId, bool),
     Position(PersonId, Position),
-    Infected(PersonId, bool),
+    Infected(PersonId, Option<StrainId>),
     Habits(PersonId, PersonHabits),
-    Tested(PersonId, bool),
-    Vaccinated(PersonId, bool),
+    /// The mask, hygiene and vaccination attitudes, without the acquaintances.
+    Attitudes(PersonId, f32, f32, f32),
+    Tested(PersonId, Option<TestResult>),
+    Immunity(PersonId, Immunity),
+    Symptoms(PersonId, Symptoms),
+    Schedule(PersonId, Schedule),
+    Job(PersonId, Job),
+}
+
+impl PersonUpdate {
+    pub fn attitudes(id: PersonId, habits: &PersonHabits) -> Self {
+        PersonUpdate::Attitudes(id, habits.mask, habits.hygiene, habits.vaccination_bias)
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct PersonId(pub u32);
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub enum PersonAction {
     Working,
     Walking(Vec<Position>, Box<PersonAction>),
-    Shopping(u32),
+    /// Doing something planned in the schedule, other than working.
+    Activity(Activity),
     AtHome,
     Partying(u32),
     Lockdown(u32),
+    Hospitalized,
+    /// Out of town until the given minute.
+    Travelling(u32),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -59,15 +120,125 @@ pub struct PersonHabits {
     pub acquaintances: HashSet<PersonId>,
 }
 
+impl PersonHabits {
+    /// How much the person trusts the public health measures, from 0 to 1.
+    pub fn trust(&self) -> f32 {
+        (self.mask + self.hygiene + self.vaccination_bias) / 3.0
+    }
+
+    /// Moves each attitude by the given amount, positive being towards following the
+    /// measures, keeping them between 0 and 1.
+    pub fn shift(&mut self, mask: f32, hygiene: f32, vaccination: f32) {
+        self.mask = (self.mask + mask).clamp(0.0, 1.0);
+        self.hygiene = (self.hygiene + hygiene).clamp(0.0, 1.0);
+        self.vaccination_bias = (self.vaccination_bias + vaccination).clamp(0.0, 1.0);
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum TestResult {
+    Positive,
+    Negative,
+}
+
+impl TestResult {
+    pub fn as_str(&self) -> &str {
+        match self {
+            TestResult::Positive => "Positive",
+            TestResult::Negative => "Negative",
+        }
+    }
+}
+
+/// How ill an infection makes someone.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Symptoms {
+    None,
+    /// Ill enough to stay home
+    Mild,
+    /// Needs a hospital bed
+    Severe,
+}
+
+/// Conditions that make an infection more likely to turn severe or deadly.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Comorbidity {
+    Diabetes,
+    HeartDisease,
+    LungDisease,
+    Obesity,
+    Immunocompromised,
+}
+
+impl Comorbidity {
+    const ALL: [Comorbidity; 5] = [
+        Comorbidity::Diabetes,
+        Comorbidity::HeartDisease,
+        Comorbidity::LungDisease,
+        Comorbidity::Obesity,
+        Comorbidity::Immunocompromised,
+    ];
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Comorbidity::Diabetes => "Diabetes",
+            Comorbidity::HeartDisease => "Heart disease",
+            Comorbidity::LungDisease => "Lung disease",
+            Comorbidity::Obesity => "Obesity",
+            Comorbidity::Immunocompromised => "Immunocompromised",
+        }
+    }
+
+    /// Share of 50 year olds with the condition, most conditions are more common the older
+    /// people get.
+    fn prevalence(&self) -> f64 {
+        match self {
+            Comorbidity::Diabetes => 0.08,
+            Comorbidity::HeartDisease => 0.06,
+            Comorbidity::LungDisease => 0.05,
+            Comorbidity::Obesity => 0.15,
+            Comorbidity::Immunocompromised => 0.02,
+        }
+    }
+
+    pub fn generate(age: u8, rng: &mut impl Rng) -> Vec<Self> {
+        Self::ALL
+            .iter()
+            .filter(|comorbidity| {
+                let chance = match comorbidity {
+                    Comorbidity::Obesity | Comorbidity::Immunocompromised => {
+                        comorbidity.prevalence()
+                    }
+                    _ => comorbidity.prevalence() * age as f64 / 50.0,
+                };
+
+                rng.gen_bool(chance.min(1.0))
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Vaccine doses and past infections protecting a person.
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+pub struct Immunity {
+    pub doses: u8,
+    pub tick_last_dose: u64,
+    pub tick_recovered: Option<u64>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Person {
     pub alive: bool,
-    pub infected: bool,
+    pub strain: Option<StrainId>,
+    pub symptoms: Symptoms,
+    pub comorbidities: Vec<Comorbidity>,
     pub lockdown: bool,
     pub tick_infected: u64,
-    pub tick_last_touched: u64,
-    pub tested: bool,
-    pub vaccinated: bool,
+    /// The latest test result, until it expires.
+    pub tested: Option<TestResult>,
+    pub tick_tested: u64,
+    pub immunity: Immunity,
     pub first_name: String,
     pub last_name: String,
     pub age: u8,
@@ -76,23 +247,31 @@ pub struct Person {
     pub position: Position,
     pub home: Position,
     pub habits: PersonHabits,
+    pub schedule: Schedule,
 }
 
 impl JobType {
-    pub fn work_hours(&self) -> Range<u32> {
+    /// Gets the hours worked, people without a job have none.
+    pub fn shift(&self) -> Option<Shift> {
         match self {
-            JobType::Doctor => 9..17,
-            JobType::Programmer => 12..22,
-            JobType::Clerk => 7..18,
-            JobType::PoliceOfficer => 6..16,
-            JobType::FireFighter => 11..21,
-            JobType::PublicServant => 11..23,
-            JobType::Chef => 14..23,
-            JobType::Teacher => 8..17,
-            JobType::Student => 8..16,
+            JobType::Doctor => Some(Shift::new(9, 17, WorkDays::EveryDay)),
+            JobType::Programmer => Some(Shift::new(12, 22, WorkDays::Weekdays)),
+            JobType::Clerk => Some(Shift::new(7, 18, WorkDays::ExceptSunday)),
+            JobType::PoliceOfficer => Some(Shift::new(22, 6, WorkDays::EveryDay)),
+            JobType::FireFighter => Some(Shift::new(20, 8, WorkDays::EveryDay)),
+            JobType::PublicServant => Some(Shift::new(11, 23, WorkDays::Weekdays)),
+            JobType::Chef => Some(Shift::new(14, 23, WorkDays::EveryDay)),
+            JobType::Teacher => Some(Shift::new(8, 17, WorkDays::SchoolDays)),
+            JobType::Student => Some(Shift::new(8, 16, WorkDays::SchoolDays)),
+            JobType::Child | JobType::Retired | JobType::Unemployed => None,
         }
     }
 
+    /// Whether people with the job are part of the workforce, employed or not.
+    pub fn in_workforce(&self) -> bool {
+        !matches!(self, JobType::Student | JobType::Child | JobType::Retired)
+    }
+
     pub fn as_str(&self) -> &str {
         match self {
             JobType::Doctor => "Doctor",
@@ -104,11 +283,15 @@ impl JobType {
             JobType::Chef => "Chef",
             JobType::Teacher => "Teacher",
             JobType::Student => "Student",
+            JobType::Child => "Child",
+            JobType::Retired => "Retired",
+            JobType::Unemployed => "Unemployed",
         }
     }
 
+    /// Generates the type of a workplace. Students go to the schools teachers work at.
     pub fn generate(rng: &mut impl Rng) -> Self {
-        match rng.gen_range(0..9) {
+        match rng.gen_range(0..8) {
             0 => JobType::Doctor,
             1 => JobType::Programmer,
             2 => JobType::Clerk,
@@ -117,28 +300,72 @@ impl JobType {
             5 => JobType::PublicServant,
             6 => JobType::Chef,
             7 => JobType::Teacher,
-            8 => JobType::Student,
             _ => unreachable!(),
         }
     }
 }
 
+impl Immunity {
+    pub fn vaccinated(&self) -> bool {
+        self.doses > 0
+    }
+
+    /// Protection against infection at *tick*, from 0 (none) to 1 (immune).
+    pub fn protection(&self, config: &ImmunityConfig, tick: u64) -> f32 {
+        let wane = |efficacy: f32, since: u64, half_life: f32| {
+            efficacy
+                * 0.5f32.powf(tick.saturating_sub(since) as f32 / days_to_ticks(half_life) as f32)
+        };
+
+        let vaccine = match self.doses {
+            0 => 0.0,
+            n if n > config.primary_doses() => config.booster_efficacy,
+            n => config.dose_efficacy[n as usize - 1],
+        };
+        let vaccine = wane(vaccine, self.tick_last_dose, config.vaccine_half_life_days);
+
+        let infection = self
+            .tick_recovered
+            .map(|recovered| {
+                wane(
+                    config.infection_efficacy,
+                    recovered,
+                    config.infection_half_life_days,
+                )
+            })
+            .unwrap_or(0.0);
+
+        1.0 - (1.0 - vaccine) * (1.0 - infection)
+    }
+
+    /// Checks if another dose can be given at *tick*. Once the primary course is done,
+    /// doses are only given as boosters while a booster campaign is running.
+    pub fn can_get_dose(&self, config: &ImmunityConfig, tick: u64, booster_campaign: bool) -> bool {
+        let since = tick.saturating_sub(self.tick_last_dose);
+
+        match self.doses {
+            0 => true,
+            n if n < config.primary_doses() => since >= days_to_ticks(config.days_between_doses),
+            _ => booster_campaign && since >= days_to_ticks(config.days_before_booster),
+        }
+    }
+}
+
 impl Person {
-    pub fn generate(rng: &mut impl Rng, home: Position, job: Job) -> Self {
+    pub fn generate(rng: &mut impl Rng, home: Position, age: u8, job: Job) -> Self {
         Person {
             alive: true,
-            infected: false,
-            vaccinated: false,
+            strain: None,
+            symptoms: Symptoms::None,
+            immunity: Immunity::default(),
             lockdown: false,
-            tested: false,
+            tested: None,
+            tick_tested: 0,
             tick_infected: 0,
-            tick_last_touched: 0,
             first_name: FIRST_NAMES.choose(rng).unwrap().to_string(),
             last_name: LAST_NAMES.choose(rng).unwrap().to_string(),
-            age: match rng.gen_range(0.0..1.0) {
-                n if n > 0.3 => rng.gen_range(18..40),
-                _ => rng.gen_range(40..80),
-            },
+            age,
+            comorbidities: Comorbidity::generate(age, rng),
             sex: rng.gen_bool(0.5),
             job,
             position: home.clone(),
@@ -150,9 +377,30 @@ impl Person {
                 socialscore: rng.gen_range(0.0..0.15),
                 acquaintances: HashSet::new(),
             },
+            schedule: Schedule::default(),
         }
     }
 
+    pub fn infected(&self) -> bool {
+        self.strain.is_some()
+    }
+
+    pub fn severe(&self) -> bool {
+        self.symptoms == Symptoms::Severe
+    }
+
+    /// Multiplier on the hospitalization and fatality rates from comorbidities.
+    pub fn risk(&self, config: &SeverityConfig) -> f64 {
+        config
+            .comorbidity_multiplier
+            .powi(self.comorbidities.len() as i32)
+    }
+
+    /// People who tested positive isolate at home, whether or not they are actually infected.
+    pub fn tested_positive(&self) -> bool {
+        self.tested == Some(TestResult::Positive)
+    }
+
     pub fn add_acquaintance(&mut self, id: PersonId) {
         self.habits.acquaintances.insert(id);
     }
@@ -165,84 +413,37 @@ impl Person {
         rng: &mut impl Rng,
     ) {
         match action {
-            PersonAction::AtHome => {
-                let work_hours = self.job.ty.work_hours();
-
-                if let Some(job_location) = &self.job.location {
-                    // leave when lob starts
-                    if world.time.hours >= work_hours.start
-                        && world.time.hours < work_hours.end
-                        // add some random chance, so everyone doesn't leave at excatly the same time
-                        && rng.gen_range(0..10) == 0
-                        && !(self.tested && self.infected)
-                    {
-                        let path = path_cache.get_path(
-                            &world.map,
-                            self.position.clone(),
-                            job_location.clone(),
-                        );
-
-                        if let Some(path) = path {
-                            *action = PersonAction::Walking(
-                                path.clone(),
-                                Box::new(PersonAction::Working),
-                            );
-                        }
-                    } else if world.time.hours >= work_hours.end {
-                        // randomly go shopping
-                        if rng.gen_range(0..1000) == 0 {
-                            if let Some(shops) = world.job_locations.get(&JobType::Clerk) {
-                                let path = path_cache.get_path(
-                                    &world.map,
-                                    self.position.clone(),
-                                    shops.choose(rng).unwrap().clone(),
-                                );
-
-                                if let Some(path) = path {
-                                    *action = PersonAction::Walking(
-                                        path.clone(),
-                                        Box::new(PersonAction::Shopping(
-                                            world.time.to_minutes() + rng.gen_range(90..120),
-                                        )),
-                                    );
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            PersonAction::Walking(path, next) => {
-                if path.len() == 0 {
-                    *action = (**next).clone();
-                }
-            }
-            PersonAction::Working => {
-                let work_hours = self.job.ty.work_hours();
+            PersonAction::AtHome | PersonAction::Working | PersonAction::Activity(_) => {
+                let time = &world.time;
+
+                // people who tested positive or have symptoms stay home, whatever their plans
+                let plan = self
+                    .schedule
+                    .current(time.hours * 60 + time.minutes)
+                    .filter(|_| self.schedule.day == time.days)
+                    .filter(|_| !self.tested_positive() && self.symptoms == Symptoms::None);
+
+                let (next, location) = match plan {
+                    Some(plan) => (plan.action(time.days), plan.location.clone()),
+                    None => (PersonAction::AtHome, self.home.clone()),
+                };
 
                 // add some random chance, so everyone doesn't leave at excatly the same time
-                if world.time.hours >= work_hours.end && rng.gen_range(0..10) == 0 {
-                    let path =
-                        path_cache.get_path(&world.map, self.position.clone(), self.home.clone());
+                if *action != next && rng.gen_range(0..10) == 0 {
+                    let path = path_cache.get_path(&world.map, self.position.clone(), location);
 
                     if let Some(path) = path {
-                        *action =
-                            PersonAction::Walking(path.clone(), Box::new(PersonAction::AtHome));
+                        *action = PersonAction::Walking(path.clone(), Box::new(next));
                     }
                 }
             }
-            PersonAction::Shopping(time) => {
-                if world.time.to_minutes() > *time {
-                    let path =
-                        path_cache.get_path(&world.map, self.position.clone(), self.home.clone());
-
-                    if let Some(path) = path {
-                        *action =
-                            PersonAction::Walking(path.clone(), Box::new(PersonAction::AtHome));
-                    }
+            PersonAction::Walking(path, next) => {
+                if path.is_empty() {
+                    *action = (**next).clone();
                 }
             }
             PersonAction::Partying(time) => {
-                let _ = time.saturating_sub(1);
+                *time = time.saturating_sub(1);
                 if *time == 0 {
                     let path =
                         path_cache.get_path(&world.map, self.position.clone(), self.home.clone());
@@ -255,7 +456,7 @@ impl Person {
             }
 
             PersonAction::Lockdown(time) => {
-                let _ = time.saturating_sub(1);
+                *time = time.saturating_sub(1);
                 if *time == 0 {
                     let path =
                         path_cache.get_path(&world.map, self.position.clone(), self.home.clone());
@@ -266,16 +467,22 @@ impl Person {
                     }
                 }
             }
+            // admission and discharge is handled by the server
+            PersonAction::Hospitalized => {}
+            // coming back is handled by the server
+            PersonAction::Travelling(_) => {}
         }
     }
 
     pub fn update(&mut self, id: PersonId, action: &mut PersonAction) -> Option<PersonUpdate> {
         match action {
-            PersonAction::Walking(path, _) => {
-                self.position = path.pop().unwrap();
+            // the path is empty when they were sent where they already are, the next action
+            // takes over on the following tick
+            PersonAction::Walking(path, _) => path.pop().map(|position| {
+                self.position = position;
 
-                Some(PersonUpdate::Position(id, self.position.clone()))
-            }
+                PersonUpdate::Position(id, self.position.clone())
+            }),
             _ => None,
         }
     }
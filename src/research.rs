@@ -0,0 +1,171 @@
+//! Both sides can research upgrades with their money. Every upgrade changes the simulation
+//! in favour of the side that researched it, some only become available after another.
+
+use crate::config::PriceConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Upgrade {
+    /// Test results come back in half the time
+    RapidTests,
+    /// Test centers test twice as many people a day
+    MassTesting,
+    /// Masks stop twice as many infections
+    StrongerMasks,
+    /// People can get their next dose in half the time
+    FasterRollout,
+    /// Supporting people in lockdown costs half as much
+    LockdownRelief,
+    /// Infections fall off less with distance
+    Airborne,
+    /// Half as many infections show mild symptoms, so people keep going out
+    Asymptomatic,
+    /// Contamination lasts twice as long
+    SurfaceSurvival,
+    /// Infections outside are as likely as inside
+    Superspreading,
+    /// Washing hands is half as effective
+    SoapResistance,
+}
+
+impl Upgrade {
+    pub const ALL: [Upgrade; 10] = [
+        Upgrade::RapidTests,
+        Upgrade::MassTesting,
+        Upgrade::StrongerMasks,
+        Upgrade::FasterRollout,
+        Upgrade::LockdownRelief,
+        Upgrade::Airborne,
+        Upgrade::Asymptomatic,
+        Upgrade::SurfaceSurvival,
+        Upgrade::Superspreading,
+        Upgrade::SoapResistance,
+    ];
+
+    /// The side that can research the upgrade.
+    pub fn side(&self) -> bool {
+        match self {
+            Upgrade::RapidTests
+            | Upgrade::MassTesting
+            | Upgrade::StrongerMasks
+            | Upgrade::FasterRollout
+            | Upgrade::LockdownRelief => false,
+            Upgrade::Airborne
+            | Upgrade::Asymptomatic
+            | Upgrade::SurfaceSurvival
+            | Upgrade::Superspreading
+            | Upgrade::SoapResistance => true,
+        }
+    }
+
+    /// The upgrade that has to be researched first, if any.
+    pub fn requires(&self) -> Option<Upgrade> {
+        match self {
+            Upgrade::MassTesting => Some(Upgrade::RapidTests),
+            Upgrade::Superspreading => Some(Upgrade::Airborne),
+            Upgrade::SoapResistance => Some(Upgrade::SurfaceSurvival),
+            _ => None,
+        }
+    }
+
+    /// Whether the upgrade can be researched after the *researched* ones.
+    pub fn unlocked(&self, researched: &HashSet<Upgrade>) -> bool {
+        self.requires()
+            .into_iter()
+            .all(|required| researched.contains(&required))
+    }
+
+    pub fn price(&self, prices: &PriceConfig) -> u32 {
+        match self {
+            Upgrade::RapidTests => prices.rapid_tests,
+            Upgrade::MassTesting => prices.mass_testing,
+            Upgrade::StrongerMasks => prices.stronger_masks,
+            Upgrade::FasterRollout => prices.faster_rollout,
+            Upgrade::LockdownRelief => prices.lockdown_relief,
+            Upgrade::Airborne => prices.airborne,
+            Upgrade::Asymptomatic => prices.asymptomatic,
+            Upgrade::SurfaceSurvival => prices.surface_survival,
+            Upgrade::Superspreading => prices.superspreading,
+            Upgrade::SoapResistance => prices.soap_resistance,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Upgrade::RapidTests => "Rapid Tests",
+            Upgrade::MassTesting => "Mass Testing",
+            Upgrade::StrongerMasks => "Stronger Masks",
+            Upgrade::FasterRollout => "Faster Rollout",
+            Upgrade::LockdownRelief => "Lockdown Relief",
+            Upgrade::Airborne => "Airborne",
+            Upgrade::Asymptomatic => "Asymptomatic",
+            Upgrade::SurfaceSurvival => "Surface Survival",
+            Upgrade::Superspreading => "Superspreading",
+            Upgrade::SoapResistance => "Soap Resistance",
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        match self {
+            Upgrade::RapidTests => "Results in half time",
+            Upgrade::MassTesting => "2x test capacity",
+            Upgrade::StrongerMasks => "Masks work 2x better",
+            Upgrade::FasterRollout => "Doses in half time",
+            Upgrade::LockdownRelief => "Lockdowns cost less",
+            Upgrade::Airborne => "Spreads further",
+            Upgrade::Asymptomatic => "Fewer show symptoms",
+            Upgrade::SurfaceSurvival => "Surfaces stay dirty",
+            Upgrade::Superspreading => "Spreads outside",
+            Upgrade::SoapResistance => "Handwashing halved",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prerequisites_come_from_the_same_side() {
+        for upgrade in &Upgrade::ALL {
+            if let Some(required) = upgrade.requires() {
+                assert_ne!(required, *upgrade);
+                assert_eq!(required.side(), upgrade.side(), "{}", upgrade.as_str());
+            }
+        }
+    }
+
+    #[test]
+    fn upgrades_are_locked_until_their_prerequisite_is_researched() {
+        let mut researched = HashSet::new();
+        assert!(Upgrade::RapidTests.unlocked(&researched));
+        assert!(!Upgrade::MassTesting.unlocked(&researched));
+
+        researched.insert(Upgrade::Airborne);
+        assert!(!Upgrade::MassTesting.unlocked(&researched));
+
+        researched.insert(Upgrade::RapidTests);
+        assert!(Upgrade::MassTesting.unlocked(&researched));
+    }
+
+    #[test]
+    fn every_upgrade_can_be_reached() {
+        for side in [false, true].iter() {
+            let mut researched = HashSet::new();
+
+            while let Some(upgrade) = Upgrade::ALL.iter().find(|upgrade| {
+                upgrade.side() == *side
+                    && !researched.contains(*upgrade)
+                    && upgrade.unlocked(&researched)
+            }) {
+                researched.insert(*upgrade);
+            }
+
+            let side_upgrades = Upgrade::ALL
+                .iter()
+                .filter(|upgrade| upgrade.side() == *side);
+            assert_eq!(researched.len(), side_upgrades.count());
+        }
+    }
+}
@@ -0,0 +1,388 @@
+//! Every person plans their day at midnight. The plan depends on their job, who they live
+//! with, their age and their habits. Anything not planned is spent at home.
+
+use crate::map::Position;
+use crate::person::{JobType, Person, PersonAction, PersonId};
+use crate::world::{Location, World};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Activity {
+    Work,
+    /// Walking a student of the household to school before work
+    DropOff,
+    Lunch,
+    Gym,
+    Restaurant,
+    Shopping,
+    /// A short stop at a public toilet while out
+    Toilet,
+    Visit(PersonId),
+    /// Leaving town for the day
+    Travel,
+}
+
+impl Activity {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Activity::Work => "Work",
+            Activity::DropOff => "School drop-off",
+            Activity::Lunch => "Lunch",
+            Activity::Gym => "Gym",
+            Activity::Restaurant => "Restaurant",
+            Activity::Shopping => "Shopping",
+            Activity::Toilet => "Public toilet",
+            Activity::Visit(_) => "Visiting a friend",
+            Activity::Travel => "Travelling",
+        }
+    }
+}
+
+/// An activity between two minutes of the day, at a location.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Plan {
+    pub start: u32,
+    pub end: u32,
+    pub activity: Activity,
+    pub location: Position,
+}
+
+impl Plan {
+    /// The action a person does when they have arrived at the location of the plan.
+    pub fn action(&self, day: u32) -> PersonAction {
+        match self.activity {
+            Activity::Work => PersonAction::Working,
+            Activity::Travel => PersonAction::Travelling(day * MINUTES_PER_DAY + self.end),
+            _ => PersonAction::Activity(self.activity.clone()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+pub struct Schedule {
+    /// The day the schedule was planned for
+    pub day: u32,
+    /// Plans sorted by time, they never overlap
+    pub plans: Vec<Plan>,
+}
+
+impl Schedule {
+    /// Gets the plan at *minute* of the day, if any.
+    pub fn current(&self, minute: u32) -> Option<&Plan> {
+        self.plans
+            .iter()
+            .find(|plan| plan.start <= minute && minute < plan.end)
+    }
+
+    fn push(&mut self, start: u32, end: u32, activity: Activity, location: Position) {
+        let free = self.plans.last().map(|plan| plan.end).unwrap_or(0);
+        let end = end.min(MINUTES_PER_DAY);
+
+        if start >= free && start < end {
+            self.plans.push(Plan {
+                start,
+                end,
+                activity,
+                location,
+            });
+        }
+    }
+
+    /// Drops or shortens the plans that would still be going on at *minute*.
+    fn cut_off(&mut self, minute: u32) {
+        self.plans.retain(|plan| plan.start < minute);
+
+        if let Some(plan) = self.plans.last_mut() {
+            plan.end = plan.end.min(minute);
+        }
+    }
+
+    /// Some people stop at the nearest public toilet after being out.
+    fn toilet_stop(&mut self, world: &World, rng: &mut impl Rng) {
+        if let Some(plan) = self.plans.last() {
+            let (start, near) = (plan.end, plan.location.clone());
+
+            if rng.gen_bool(0.4) {
+                if let Some(toilet) = world.nearest_toilet(&near) {
+                    self.push(start, start + 10, Activity::Toilet, toilet.clone());
+                }
+            }
+        }
+    }
+
+    /// Plans the *day* for *person*.
+    pub fn generate(person: &Person, world: &World, day: u32, rng: &mut impl Rng) -> Self {
+        let mut schedule = Self {
+            day,
+            plans: Vec::new(),
+        };
+
+        let calendar = &world.calendar;
+        // people without a job or workplace never work
+        let shift = person.job.location.as_ref().and(person.job.ty.shift());
+        let night_shift = matches!(&shift, Some(shift) if shift.start > shift.end);
+        let works_on = |day| matches!(&shift, Some(shift) if shift.works_on(day, calendar));
+        // young children don't go anywhere on their own
+        let independent = person.age >= 12;
+        let day_off = calendar.is_day_off(day) || calendar.is_day_off(day + 1);
+        let random_location = |locations: Option<&Vec<Position>>, rng: &mut _| {
+            locations.and_then(|locations| locations.choose(rng).cloned())
+        };
+        let restaurants = world.job_locations.get(&JobType::Chef);
+        let gyms = world
+            .locations
+            .iter()
+            .filter(|(_, location)| **location == Location::Gym)
+            .map(|(position, _)| position.clone())
+            .collect::<Vec<_>>();
+
+        // finish the night shift that started yesterday
+        if let (Some(job_location), Some(shift)) = (&person.job.location, &shift) {
+            if night_shift && day > 0 && works_on(day - 1) {
+                schedule.push(0, shift.end * 60, Activity::Work, job_location.clone());
+            }
+        }
+
+        if calendar.is_holiday(day) && rng.gen_bool(calendar.travel_chance) {
+            if let Some(exit) = world.exits().choose(rng) {
+                let leave = rng.gen_range(8 * 60..10 * 60);
+                schedule.push(leave, 20 * 60, Activity::Travel, exit.clone());
+
+                return schedule;
+            }
+        }
+
+        let mut free_from = 9 * 60;
+
+        match (&person.job.location, &shift) {
+            (Some(job_location), Some(shift)) if !night_shift && works_on(day) => {
+                let start = shift.start * 60;
+                let end = shift.end * 60;
+
+                // walk the students of the household to school first
+                let school = world
+                    .people
+                    .values()
+                    .filter(|p| p.home == person.home && p.job.ty == JobType::Student)
+                    .filter_map(|p| p.job.location.clone())
+                    .next();
+
+                if let (Some(school), false) = (school, person.job.ty == JobType::Student) {
+                    if shift.start >= 8 {
+                        schedule.push(7 * 60 + 30, 8 * 60, Activity::DropOff, school);
+                    }
+                }
+
+                // long shifts have a lunch break, some go out to eat
+                let lunch_location = if end - start >= 8 * 60 {
+                    if rng.gen_bool(0.5) {
+                        random_location(restaurants, rng)
+                    } else {
+                        Some(job_location.clone())
+                    }
+                } else {
+                    None
+                };
+
+                match lunch_location {
+                    Some(lunch_location) => {
+                        let lunch = (start + end) / 2;
+                        schedule.push(start, lunch, Activity::Work, job_location.clone());
+                        schedule.push(lunch, lunch + 45, Activity::Lunch, lunch_location);
+                        schedule.push(lunch + 45, end, Activity::Work, job_location.clone());
+                    }
+                    None => schedule.push(start, end, Activity::Work, job_location.clone()),
+                }
+
+                free_from = end + 30;
+            }
+            _ => {
+                // a day without work leaves time for errands
+                if independent && rng.gen_bool(0.5) {
+                    if let Some(shop) =
+                        random_location(world.job_locations.get(&JobType::Clerk), rng)
+                    {
+                        let start = rng.gen_range(10 * 60..15 * 60);
+                        schedule.push(
+                            start,
+                            start + rng.gen_range(60..120),
+                            Activity::Shopping,
+                            shop,
+                        );
+                        schedule.toilet_stop(world, rng);
+                    }
+                }
+            }
+        }
+
+        // younger people are more likely to work out
+        if independent && rng.gen_bool((1.0 - person.age as f64 / 80.0).max(0.0) * 0.5) {
+            if let Some(gym) = gyms.choose(rng) {
+                let start = free_from.max(17 * 60);
+                schedule.push(start, start + 60, Activity::Gym, gym.clone());
+                schedule.toilet_stop(world, rng);
+                free_from = start + 90;
+            }
+        }
+
+        let evening = free_from.max(19 * 60);
+        let social = person.habits.socialscore as f64;
+
+        if independent && day_off && rng.gen_bool((0.3 + social * 3.0).min(1.0)) {
+            // weekend parties at friends' places
+            if let Some(friend) = person.habits.acquaintances.iter().choose(rng) {
+                let home = world.people[friend].home.clone();
+                let end = evening + rng.gen_range(120..240);
+                schedule.push(evening, end, Activity::Visit(friend.clone()), home);
+            }
+        } else if independent && rng.gen_bool((social * 2.0).min(1.0)) {
+            if let Some(restaurant) = random_location(restaurants, rng) {
+                schedule.push(evening, evening + 90, Activity::Restaurant, restaurant);
+            }
+        }
+
+        // start the night shift, whatever else was planned for the evening has to end by then
+        if let (Some(job_location), Some(shift)) = (&person.job.location, &shift) {
+            if night_shift && works_on(day) {
+                schedule.cut_off(shift.start * 60);
+                schedule.push(
+                    shift.start * 60,
+                    MINUTES_PER_DAY,
+                    Activity::Work,
+                    job_location.clone(),
+                );
+            }
+        }
+
+        schedule
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    /// Schedules of everyone in a generated town for the first week.
+    fn week() -> (World, Vec<(PersonId, Schedule)>) {
+        let world = World::generate_for_test(&Config::default(), 1);
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut schedules = Vec::new();
+
+        for day in 0..7 {
+            for (id, person) in &world.people {
+                schedules.push((
+                    id.clone(),
+                    Schedule::generate(person, &world, day, &mut rng),
+                ));
+            }
+        }
+
+        (world, schedules)
+    }
+
+    #[test]
+    fn plans_are_sorted_and_stay_within_the_day() {
+        let (_, schedules) = week();
+
+        for (_, schedule) in &schedules {
+            for plan in &schedule.plans {
+                assert!(plan.start < plan.end, "{:?}", schedule);
+                assert!(plan.end <= MINUTES_PER_DAY, "{:?}", schedule);
+            }
+
+            for plans in schedule.plans.windows(2) {
+                assert!(plans[0].end <= plans[1].start, "{:?}", schedule);
+            }
+        }
+    }
+
+    #[test]
+    fn workers_start_their_shift_at_work() {
+        let (world, schedules) = week();
+        let calendar = &world.calendar;
+
+        for (id, schedule) in &schedules {
+            let person = &world.people[id];
+            let day = schedule.day;
+            let (location, shift) = match (&person.job.location, person.job.ty.shift()) {
+                (Some(location), Some(shift)) => (location, shift),
+                _ => continue,
+            };
+
+            // people might leave town on holidays instead
+            if !shift.works_on(day, calendar) || calendar.is_holiday(day) {
+                continue;
+            }
+
+            let start = shift.start * 60;
+            let at_work = schedule.plans.iter().any(|plan| {
+                plan.activity == Activity::Work && plan.start == start && plan.location == *location
+            });
+
+            assert!(at_work, "{:?} {:?}", person.job, schedule);
+        }
+    }
+
+    #[test]
+    fn schools_are_closed_on_school_holidays() {
+        let (world, schedules) = week();
+        let school_holidays = &world.calendar.school_holidays;
+        assert!(!school_holidays.is_empty());
+
+        for (id, schedule) in &schedules {
+            let job = &world.people[id].job;
+            let works = schedule
+                .plans
+                .iter()
+                .any(|plan| plan.activity == Activity::Work);
+
+            if school_holidays.contains(&schedule.day) && job.location.is_some() {
+                match job.ty {
+                    JobType::Student | JobType::Teacher => assert!(!works, "{:?}", schedule),
+                    JobType::Programmer => assert!(works, "{:?}", schedule),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn young_children_only_go_out_with_their_family() {
+        let (world, schedules) = week();
+
+        for (id, schedule) in &schedules {
+            if world.people[id].age >= 12 {
+                continue;
+            }
+
+            for plan in &schedule.plans {
+                assert!(
+                    matches!(
+                        plan.activity,
+                        Activity::Work | Activity::Lunch | Activity::Travel
+                    ),
+                    "{:?}",
+                    schedule
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn schedules_without_a_workplace_have_no_work() {
+        let (world, schedules) = week();
+
+        for (id, schedule) in &schedules {
+            if world.people[id].job.location.is_none() {
+                assert!(schedule
+                    .plans
+                    .iter()
+                    .all(|plan| plan.activity != Activity::Work));
+            }
+        }
+    }
+}
@@ -0,0 +1,391 @@
+//! Decides when a game is over and who won it, and sums up how it went for the results
+//! screen both players see at the end.
+
+use crate::config::{Config, TICKS_PER_DAY};
+use crate::person::PersonId;
+use crate::server::CommandKind;
+use crate::world::World;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum WinReason {
+    /// Enough people were infected when time ran out
+    Infections,
+    /// Enough people died
+    Deaths,
+    /// Nobody was infected anymore
+    Eradication,
+    /// The president held out until the end
+    TimeLimit,
+}
+
+impl WinReason {
+    pub fn as_str(&self) -> &str {
+        match self {
+            WinReason::Infections => "The city was overrun by infections",
+            WinReason::Deaths => "Too many people died",
+            WinReason::Eradication => "The virus was eradicated",
+            WinReason::TimeLimit => "The city held out until the end",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameResult {
+    /// The side that won
+    pub winner: bool,
+    pub reason: WinReason,
+    pub days: u32,
+    pub population: u32,
+    /// People infected when the game ended
+    pub infected: u32,
+    pub recovered: u32,
+    pub deaths: u32,
+    pub tested_positive: u32,
+    pub vaccinated: u32,
+    pub strains: u32,
+    pub gdp: f32,
+    pub unemployment: f32,
+    pub history: History,
+}
+
+impl GameResult {
+    /// Checks the win conditions of the *config*, the game is over once this returns a
+    /// result.
+    pub fn check(world: &World, config: &Config, history: &History) -> Option<Self> {
+        let mut result = Self {
+            winner: false,
+            reason: WinReason::TimeLimit,
+            days: world.time.days,
+            population: world.people.len() as u32,
+            infected: 0,
+            recovered: 0,
+            deaths: 0,
+            tested_positive: 0,
+            vaccinated: 0,
+            strains: world.strains.len() as u32,
+            gdp: world.economy.gdp,
+            unemployment: world.economy.unemployment,
+            history: History::default(),
+        };
+
+        for person in world.people.values() {
+            if !person.alive {
+                result.deaths += 1;
+                continue;
+            }
+
+            if person.infected() {
+                result.infected += 1;
+            }
+
+            if person.immunity.tick_recovered.is_some() {
+                result.recovered += 1;
+            }
+
+            if person.tested_positive() {
+                result.tested_positive += 1;
+            }
+
+            if person.immunity.doses > 0 {
+                result.vaccinated += 1;
+            }
+        }
+
+        // a town without people still needs a share to compare against
+        let population = result.population.max(1) as f32;
+        let win = &config.win;
+
+        let (winner, reason) = if result.deaths as f32 / population >= win.death_share {
+            (true, WinReason::Deaths)
+        } else if win.eradication && result.infected == 0 {
+            (false, WinReason::Eradication)
+        } else if world.time.days < config.game.length_days {
+            return None;
+        } else if result.infected as f32 / population >= win.infection_share {
+            (true, WinReason::Infections)
+        } else {
+            (false, WinReason::TimeLimit)
+        };
+
+        result.winner = winner;
+        result.reason = reason;
+        result.history = history.clone();
+
+        Some(result)
+    }
+}
+
+/// How many people were in each stage of the disease at one point in time.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Sample {
+    pub tick: u64,
+    pub susceptible: u32,
+    pub infected: u32,
+    pub recovered: u32,
+    pub dead: u32,
+}
+
+/// A person getting infected, and who passed it on if it was another person.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Case {
+    pub person: PersonId,
+    pub source: Option<PersonId>,
+    pub day: u32,
+}
+
+/// Everything the session records as the game goes, so the results screen can show how it
+/// played out.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    pub samples: Vec<Sample>,
+    /// Money spent on each kind of command, by the side that spent it
+    pub spent: HashMap<(bool, CommandKind), u32>,
+    pub cases: Vec<Case>,
+}
+
+impl History {
+    pub fn sample(&mut self, world: &World, tick: u64) {
+        let mut sample = Sample {
+            tick,
+            susceptible: 0,
+            infected: 0,
+            recovered: 0,
+            dead: 0,
+        };
+
+        for person in world.people.values() {
+            if !person.alive {
+                sample.dead += 1;
+            } else if person.infected() {
+                sample.infected += 1;
+            } else if person.immunity.tick_recovered.is_some() {
+                sample.recovered += 1;
+            } else {
+                sample.susceptible += 1;
+            }
+        }
+
+        self.samples.push(sample);
+    }
+
+    pub fn spend(&mut self, side: bool, kind: CommandKind, amount: u32) {
+        *self.spent.entry((side, kind)).or_default() += amount;
+    }
+
+    pub fn infect(&mut self, person: PersonId, source: Option<PersonId>, tick: u64) {
+        self.cases.push(Case {
+            person,
+            source,
+            day: (tick / TICKS_PER_DAY) as u32,
+        });
+    }
+
+    /// The average amount of people infected by the people that got infected on each day.
+    /// The last days are underestimated, as their cases haven't had the time to spread it.
+    pub fn r_numbers(&self) -> Vec<f32> {
+        let days = self
+            .cases
+            .iter()
+            .map(|case| case.day + 1)
+            .max()
+            .unwrap_or(0);
+        let mut infected = vec![0; days as usize];
+        let mut passed_on = vec![0; days as usize];
+        let mut day_infected = HashMap::new();
+
+        for case in &self.cases {
+            infected[case.day as usize] += 1;
+            day_infected.insert(&case.person, case.day);
+        }
+
+        for source in self.cases.iter().filter_map(|case| case.source.as_ref()) {
+            if let Some(day) = day_infected.get(source) {
+                passed_on[*day as usize] += 1;
+            }
+        }
+
+        infected
+            .iter()
+            .zip(passed_on)
+            .map(|(infected, passed_on)| passed_on as f32 / (*infected).max(1) as f32)
+            .collect()
+    }
+
+    /// The people that infected the most others, along with how many, most first.
+    pub fn top_spreaders(&self, count: usize) -> Vec<(PersonId, u32)> {
+        let mut spread: HashMap<&PersonId, u32> = HashMap::new();
+
+        for source in self.cases.iter().filter_map(|case| case.source.as_ref()) {
+            *spread.entry(source).or_default() += 1;
+        }
+
+        let mut spreaders = spread
+            .into_iter()
+            .map(|(id, infected)| (id.clone(), infected))
+            .collect::<Vec<_>>();
+        spreaders.sort_by_key(|(id, infected)| (std::cmp::Reverse(*infected), id.0));
+        spreaders.truncate(count);
+
+        spreaders
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A history of infections, given as the person, who infected them and the tick.
+    fn history(cases: &[(u32, Option<u32>, u64)]) -> History {
+        let mut history = History::default();
+
+        for (person, source, tick) in cases {
+            history.infect(PersonId(*person), source.map(PersonId), *tick);
+        }
+
+        history
+    }
+
+    #[test]
+    fn cases_are_bucketed_by_day() {
+        let history = history(&[
+            (1, None, 0),
+            (2, None, TICKS_PER_DAY - 1),
+            (3, None, TICKS_PER_DAY),
+            (4, None, 3 * TICKS_PER_DAY + 10),
+        ]);
+        let days = history
+            .cases
+            .iter()
+            .map(|case| case.day)
+            .collect::<Vec<_>>();
+
+        assert_eq!(days, vec![0, 0, 1, 3]);
+    }
+
+    #[test]
+    fn r_numbers_credit_the_day_the_source_was_infected() {
+        let day = TICKS_PER_DAY;
+        let history = history(&[
+            (1, None, 0),
+            (2, None, 0),
+            (3, Some(1), day),
+            (4, Some(1), day),
+            (5, Some(3), 2 * day),
+            // infected by someone that never showed up as a case, like a traveller
+            (6, Some(99), 2 * day),
+        ]);
+
+        assert_eq!(history.r_numbers(), vec![1.0, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn r_numbers_cover_days_without_cases() {
+        let history = history(&[(1, None, 0), (2, Some(1), 2 * TICKS_PER_DAY)]);
+
+        assert_eq!(history.r_numbers(), vec![1.0, 0.0, 0.0]);
+        assert!(History::default().r_numbers().is_empty());
+    }
+
+    #[test]
+    fn top_spreaders_are_sorted_by_infections_then_id() {
+        let history = history(&[
+            (10, Some(3), 0),
+            (11, Some(3), 0),
+            (12, Some(1), 0),
+            (13, Some(2), 0),
+            (14, Some(2), 0),
+            (15, Some(4), 0),
+            (16, None, 0),
+        ]);
+
+        assert_eq!(
+            history.top_spreaders(3),
+            vec![(PersonId(2), 2), (PersonId(3), 2), (PersonId(1), 1)]
+        );
+        assert_eq!(history.top_spreaders(10).len(), 4);
+    }
+
+    /// A world after *days* in which *infected* and *dead* people are the first ones by id,
+    /// and nobody else is infected.
+    fn world(config: &Config, days: u32, infected: usize, dead: usize) -> World {
+        let mut world = World::generate_for_test(config, 0);
+        let mut ids = world.people.keys().cloned().collect::<Vec<_>>();
+        ids.sort_by_key(|id| id.0);
+
+        for (index, id) in ids.iter().enumerate() {
+            let person = world.people.get_mut(id).unwrap();
+            person.alive = index >= infected + dead || index < infected;
+            person.strain = if index < infected {
+                Some(crate::virus::StrainId(0))
+            } else {
+                None
+            };
+        }
+
+        world.time.set_minutes(days * TICKS_PER_DAY as u32);
+        world
+    }
+
+    #[test]
+    fn games_go_on_until_the_last_day() {
+        let config = Config::default();
+        let world = world(&config, config.game.length_days - 1, 300, 0);
+
+        assert!(GameResult::check(&world, &config, &History::default()).is_none());
+    }
+
+    #[test]
+    fn deaths_end_the_game_early() {
+        let config = Config::default();
+        let population = config.population.size as f32;
+        let dead = (population * config.win.death_share).ceil() as usize;
+        let result =
+            GameResult::check(&world(&config, 0, 1, dead), &config, &History::default()).unwrap();
+
+        assert!(result.winner);
+        assert_eq!(result.reason, WinReason::Deaths);
+        assert_eq!(result.deaths, dead as u32);
+    }
+
+    #[test]
+    fn eradication_ends_the_game_early() {
+        let mut config = Config::default();
+        let world = world(&config, 0, 0, 0);
+        let result = GameResult::check(&world, &config, &History::default()).unwrap();
+
+        assert!(!result.winner);
+        assert_eq!(result.reason, WinReason::Eradication);
+
+        config.win.eradication = false;
+        assert!(GameResult::check(&world, &config, &History::default()).is_none());
+    }
+
+    #[test]
+    fn infections_decide_the_game_when_time_runs_out() {
+        let config = Config::default();
+        let days = config.game.length_days;
+        let population = config.population.size as f32;
+        let share = (population * config.win.infection_share).ceil() as usize;
+
+        let result = GameResult::check(
+            &world(&config, days, share, 0),
+            &config,
+            &History::default(),
+        )
+        .unwrap();
+        assert!(result.winner);
+        assert_eq!(result.reason, WinReason::Infections);
+        assert_eq!(result.infected, share as u32);
+
+        let result = GameResult::check(
+            &world(&config, days, share - 1, 0),
+            &config,
+            &History::default(),
+        )
+        .unwrap();
+        assert!(!result.winner);
+        assert_eq!(result.reason, WinReason::TimeLimit);
+    }
+}
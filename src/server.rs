@@ -1,11 +1,22 @@
-use crate::map::{Map, Position, Tile};
+use crate::agent::{AgentPlayer, Seat};
+use crate::bot::Difficulty;
+use crate::config::{days_to_ticks, Config, CooldownConfig, PriceConfig, TICKS_PER_DAY};
+use crate::map::{Contamination, Map, Position, Tile};
 use crate::map_generation::MapGenerationSettings;
-use crate::person::{Person, PersonAction, PersonId, PersonUpdate};
-use crate::world::{Location, World};
+use crate::person::{
+    Job, JobType, Person, PersonAction, PersonId, PersonUpdate, Symptoms, TestResult,
+};
+use crate::research::Upgrade;
+use crate::schedule::Schedule;
+use crate::score::{GameResult, History};
+use crate::spatial::SpatialIndex;
+use crate::virus::{self, Mutation, Strain, StrainId};
+use crate::world::{Economy, Hospital, Location, World, BEDS_PER_DOCTOR, HOSPITAL_BEDS};
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::collections::VecDeque;
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::io::Interest;
@@ -16,6 +27,11 @@ use tokio::net::{
 };
 use tokio::time::sleep;
 
+/// Ticks after being infected at which symptoms show, if there are any.
+const SYMPTOM_ONSET_TICKS: u64 = 2880;
+/// Chance that a new infection spontaneously mutates into a new strain.
+const MUTATION_CHANCE: f64 = 0.002;
+
 pub struct PathCache {
     paths: HashMap<(Position, Position), Option<Vec<Position>>>,
 }
@@ -87,6 +103,23 @@ impl PathCache {
     }
 }
 
+/// A test center takes samples from everyone passing by, but can only analyse a limited
+/// amount of them each day. The rest wait in line.
+#[derive(Default)]
+pub struct TestCenter {
+    pub queue: VecDeque<PersonId>,
+    pub day: u64,
+    pub tested_today: u32,
+}
+
+/// A test that has been analysed, but where the result hasn't come back yet.
+pub struct PendingTest {
+    pub person: PersonId,
+    pub result: TestResult,
+    /// Tick at which the result comes back
+    pub ready: u64,
+}
+
 pub struct GameSession {
     pub player1: PlayerSession,
     pub player2: PlayerSession,
@@ -97,8 +130,30 @@ pub struct GameSession {
     pub people_actions: HashMap<PersonId, PersonAction>,
     pub path_cache: PathCache,
     pub receiver: Receiver<PlayerUpdate>,
-    pub test_centers: HashSet<Position>,
+    /// Amount of ticks people have been close to each other, keyed by the person that might
+    /// get infected and the person that might infect them.
+    pub contacts: HashMap<(PersonId, PersonId), u32>,
+    pub test_centers: HashMap<Position, TestCenter>,
+    pub pending_tests: Vec<PendingTest>,
+    /// People that are either waiting in line at a test center or for their result.
+    pub awaiting_test: HashSet<PersonId>,
     pub vaccine_centers: HashSet<Position>,
+    /// Mask, antivax and hygiene campaigns that are still running.
+    pub campaigns: HashSet<Position>,
+    /// People whose attitudes changed since they were last sent to the players, which
+    /// happens every hour.
+    pub swayed: HashSet<PersonId>,
+    /// The public toilet or shop each of their tiles belongs to, by the position of the
+    /// toilet or the door of the shop. Viral load left on any of them is spread over all of
+    /// them in [`Map::contamination`].
+    pub hotspots: HashMap<Position, Position>,
+    /// Tick at which the current booster campaign ends.
+    pub booster_campaign: u64,
+    /// Jobs people lost in an economic crash, they get them back over time.
+    pub laid_off: HashMap<PersonId, Job>,
+    /// How the game has played out so far, for the results screen.
+    pub history: History,
+    pub config: Config,
 }
 
 impl GameSession {
@@ -109,17 +164,11 @@ impl GameSession {
         // Create payload
         let network_payload = NetworkPayload::create(&self, &self.player1, updates.clone());
         let serialized_payload = bincode::serialize(&network_payload).unwrap();
-        let payload_size = (serialized_payload.len() as u32).to_be_bytes();
-
-        self.player1.socket.write_all(&payload_size).await?;
-        self.player1.socket.write_all(&serialized_payload).await?;
+        self.player1.send(&serialized_payload).await?;
 
         let network_payload = NetworkPayload::create(&self, &self.player2, updates);
         let serialized_payload = bincode::serialize(&network_payload).unwrap();
-        let payload_size = (serialized_payload.len() as u32).to_be_bytes();
-
-        self.player2.socket.write_all(&payload_size).await?;
-        self.player2.socket.write_all(&serialized_payload).await?;
+        self.player2.send(&serialized_payload).await?;
 
         Ok(())
     }
@@ -127,132 +176,203 @@ impl GameSession {
     pub async fn update(&mut self, rng: &mut impl Rng) -> Vec<StateUpdate> {
         self.world.time.set_minutes(self.tick_count as u32);
 
-        self.player1.money += 1;
-        self.player2.money += 1;
-
-        self.handle_players().await;
+        self.handle_players(rng).await;
 
         let mut updates = Vec::new();
-        let mut person_locations: HashMap<Position, Vec<PersonId>> = HashMap::new();
+        self.update_layoffs(rng, &mut updates);
+        self.update_schedules(rng, &mut updates);
+
+        let mut index = SpatialIndex::new(self.config.transmission.contact_radius);
 
         for (id, person) in &self.world.people {
             let action = self.people_actions.get_mut(id).unwrap();
-            person_locations
-                .entry(person.position.clone())
-                .or_insert(Vec::new())
-                .push(id.clone());
+
+            if !person.alive {
+                continue;
+            }
+
+            // people out of town can't meet anyone
+            if !matches!(action, PersonAction::Travelling(_)) {
+                index.insert(person.position.clone(), id.clone());
+            }
+
             person.update_action(&self.world, &mut self.path_cache, action, rng);
         }
 
-        for test_center in &self.test_centers {
-            if let Some(persons) = person_locations.get(test_center) {
-                for person in persons {
-                    self.world.people.get_mut(person).unwrap().tested = true;
-                    updates.push(StateUpdate::PersonUpdate(PersonUpdate::Tested(
-                        person.clone(),
-                        true,
+        self.enter_buildings(&index);
+        self.update_testing(&index, rng, &mut updates);
+
+        let booster_campaign = self.booster_campaign > self.tick_count;
+        let mut immunity = self.config.immunity.clone();
+
+        if self.researched(Upgrade::FasterRollout) {
+            immunity.days_between_doses /= 2.0;
+        }
+
+        for vaccine_center in &self.vaccine_centers {
+            for id in index.at(vaccine_center) {
+                let person = self.world.people.get_mut(id).unwrap();
+
+                // Only those willing to get vaccinated take the dose
+                if person
+                    .immunity
+                    .can_get_dose(&immunity, self.tick_count, booster_campaign)
+                    && person.habits.vaccination_bias > rng.gen_range(0.0..1.0)
+                {
+                    person.immunity.doses += 1;
+                    person.immunity.tick_last_dose = self.tick_count;
+                    updates.push(StateUpdate::PersonUpdate(PersonUpdate::Immunity(
+                        id.clone(),
+                        person.immunity.clone(),
                     )));
                 }
             }
         }
 
-        for vaccine_center in &self.vaccine_centers {
-            if let Some(persons) = person_locations.get(vaccine_center) {
-                for person in persons {
-                    self.world.people.get_mut(person).unwrap().vaccinated = true;
-                    updates.push(StateUpdate::PersonUpdate(PersonUpdate::Vaccinated(
-                        person.clone(),
-                        true,
-                    )));
+        let stronger_masks = self.researched(Upgrade::StrongerMasks);
+        let airborne = self.researched(Upgrade::Airborne);
+        let superspreading = self.researched(Upgrade::Superspreading);
+        let handwashing = self.handwashing();
+        let config = &self.config.transmission;
+
+        // Count for how long everyone has been close to each other, contacts that are broken
+        // off start over
+        let mut contacts = HashMap::new();
+
+        for (id, person) in &self.world.people {
+            if !person.alive {
+                continue;
+            }
+
+            for (other_id, distance) in index.near(&person.position, config.contact_radius) {
+                if other_id != id {
+                    let key = (id.clone(), other_id.clone());
+                    let ticks = self.contacts.get(&key).copied().unwrap_or(0) + 1;
+                    contacts.insert(key, (ticks, distance));
                 }
             }
         }
 
-        for (position, persons) in &person_locations {
-            let tile = self.world.map.get_tile(position);
+        for ((id, other_id), (ticks, distance)) in &contacts {
+            let other_person = self.world.people.get(other_id).unwrap().clone();
+            let tile = self.world.map.get_tile(&self.world.people[id].position);
 
-            for other_id in persons {
-                let other_person = self.world.people.get(other_id).unwrap().clone();
+            let mut infection_chance: f32 = self.config.game.infection_chance;
 
-                for id in persons {
-                    // If it's the same person as the inital loop skip
-                    if other_id == id {
-                        continue;
-                    }
+            let person = self.world.people.get_mut(id).unwrap();
 
-                    let mut infection_chance: f32 = 0.15;
+            // Being further away from each other, and only having been close for a short
+            // while, makes it less likely to get infected
+            let falloff = if airborne {
+                config.distance_falloff.sqrt()
+            } else {
+                config.distance_falloff
+            };
+            infection_chance *= falloff.powi(*distance as i32);
+            infection_chance *= (*ticks as f32 / config.exposure_ticks as f32).min(1.0);
 
-                    let person = self.world.people.get_mut(id).unwrap();
+            infection_chance *= if tile.is_indoors() || superspreading {
+                config.indoor_multiplier
+            } else {
+                config.outdoor_multiplier
+            };
 
-                    if person.tick_last_touched - self.tick_count > 20 {
-                        person.tick_last_touched = self.tick_count;
-                    } else {
-                        continue;
-                    }
+            // False sex have better immune systems than true sex
+            if !person.sex {
+                infection_chance *= 0.9;
+            }
 
-                    // False sex have better immune systems than true sex
-                    if !person.sex {
-                        infection_chance *= 0.9;
-                    }
+            if person.tested_positive() {
+                infection_chance *= 0.05;
+            }
 
-                    if person.infected && person.tested {
-                        infection_chance *= 0.05;
-                    }
+            // Washing hands keeps the virus from being passed on, and from being picked up
+            infection_chance *= 1.0 - handwashing * person.habits.hygiene;
+            infection_chance *= 1.0 - handwashing * other_person.habits.hygiene;
 
-                    // Check if you and the other people are wearing masks
-                    if person.habits.mask > rng.gen_range(0.0..1.0) {
-                        if other_person.habits.mask > rng.gen_range(0.0..1.0) {
-                            infection_chance /= 2.0;
-                        } else {
-                            infection_chance /= 10.0;
-                        }
-                    }
+            // Check if you and the other people are wearing masks
+            if person.habits.mask > rng.gen_range(0.0..1.0) {
+                if other_person.habits.mask > rng.gen_range(0.0..1.0) {
+                    infection_chance /= 2.0;
+                } else {
+                    infection_chance /= 10.0;
+                }
 
-                    // The older you are the worse your immune system is
-                    infection_chance *= 1.0 + person.age as f32 / 100.0;
+                if stronger_masks {
+                    infection_chance /= 2.0;
+                }
+            }
 
-                    if person.vaccinated {
-                        infection_chance *= 0.05;
-                    }
+            // The older you are the worse your immune system is
+            infection_chance *= self.config.severity.band(person.age).susceptibility;
 
-                    if other_person.infected && infection_chance > rng.gen_range(0.0..100.0) {
-                        person.infected = true;
-                        person.tick_infected = self.tick_count;
-                        updates.push(StateUpdate::PersonUpdate(PersonUpdate::Infected(
-                            id.clone(),
-                            person.infected,
-                        )));
-
-                    // If you have been infected for more than a week
-                    } else if person.infected && self.tick_count - person.tick_infected > 604800 {
-                        // If true, die, otherwise live.
-                        if rng.gen_bool((person.age as f64) / 500.0) {
-                            person.alive = false;
-                            updates.push(StateUpdate::PersonUpdate(PersonUpdate::LifeStatus(
-                                id.clone(),
-                                person.alive,
-                            )));
-                        } else {
-                            person.infected = false;
-                            updates.push(StateUpdate::PersonUpdate(PersonUpdate::Infected(
-                                id.clone(),
-                                person.infected,
-                            )));
+            if let (Some(strain_id), None) = (other_person.strain, person.strain) {
+                let strain = &self.world.strains[&strain_id];
+
+                infection_chance *= strain.transmissibility;
+
+                // Vaccines and past infections protect you, but the strain gets
+                // around part of that protection
+                let protection = person
+                    .immunity
+                    .protection(&self.config.immunity, self.tick_count);
+                infection_chance *= 1.0 - protection * (1.0 - strain.vaccine_escape);
+
+                if infection_chance > rng.gen_range(0.0..100.0) {
+                    let mut strain_id = strain_id;
+
+                    // Every infection is a chance for the virus to mutate
+                    if rng.gen_bool(MUTATION_CHANCE) {
+                        let mutation = Mutation::generate(rng);
+
+                        if let Some(new_strain) =
+                            virus::mutate(&mut self.world.strains, strain_id, &mutation, rng)
+                        {
+                            updates.push(StateUpdate::StrainUpdate(
+                                new_strain,
+                                self.world.strains[&new_strain].clone(),
+                            ));
+                            strain_id = new_strain;
                         }
                     }
 
-                    // See if they become friends
-                    if person.habits.socialscore > rng.gen_range(0.0..1.0)
-                        && !person.habits.acquaintances.contains(other_id)
-                    {
-                        person.add_acquaintance(other_id.clone());
-                        updates.push(StateUpdate::PersonUpdate(PersonUpdate::Habits(
-                            id.clone(),
-                            person.habits.clone(),
-                        )));
-                    }
+                    person.strain = Some(strain_id);
+                    person.tick_infected = self.tick_count;
+                    self.history
+                        .infect(id.clone(), Some(other_id.clone()), self.tick_count);
+                    updates.push(StateUpdate::PersonUpdate(PersonUpdate::Infected(
+                        id.clone(),
+                        person.strain,
+                    )));
                 }
             }
+
+            // See if they become friends after spending some time together
+            if *ticks == config.exposure_ticks
+                && person.habits.socialscore > rng.gen_range(0.0..1.0)
+                && !person.habits.acquaintances.contains(other_id)
+            {
+                person.add_acquaintance(other_id.clone());
+                updates.push(StateUpdate::PersonUpdate(PersonUpdate::Habits(
+                    id.clone(),
+                    person.habits.clone(),
+                )));
+            }
+        }
+
+        self.contacts = contacts
+            .into_iter()
+            .map(|(key, (ticks, _))| (key, ticks))
+            .collect();
+        self.update_contamination(rng, &mut updates);
+        self.update_opinions(&index, &mut updates);
+        self.update_travel(rng, &mut updates);
+        self.update_hospitals(&mut updates);
+        self.update_disease(rng, &mut updates);
+        self.update_economy(&mut updates);
+
+        if self.world.time.minutes == 0 {
+            self.history.sample(&self.world, self.tick_count);
         }
 
         for (id, person) in &mut self.world.people {
@@ -267,7 +387,718 @@ impl GameSession {
         updates
     }
 
-    pub async fn handle_players(&mut self) {
+    /// Moves people that have arrived at the door of a building into the room with the fewest
+    /// people in it. When every room is full they have to wait at the door.
+    fn enter_buildings(&mut self, index: &SpatialIndex) {
+        let people_per_room = self.config.buildings.people_per_room;
+        let mut occupants: HashMap<Position, usize> = HashMap::new();
+
+        for (id, person) in &self.world.people {
+            let action = self.people_actions.get_mut(id).unwrap();
+
+            if !person.alive || matches!(action, PersonAction::Walking(_, _)) {
+                continue;
+            }
+
+            let rooms = match self.world.map.rooms.get(&person.position) {
+                Some(rooms) => rooms,
+                None => continue,
+            };
+
+            let room = rooms
+                .iter()
+                .map(|room| {
+                    let count = *occupants
+                        .entry(room.clone())
+                        .or_insert_with(|| index.at(room).count());
+                    (room, count)
+                })
+                .min_by_key(|(_, count)| *count);
+
+            if let Some((room, count)) = room {
+                if count >= people_per_room {
+                    continue;
+                }
+
+                let path = self.path_cache.get_path(
+                    &self.world.map,
+                    person.position.clone(),
+                    room.clone(),
+                );
+
+                if let Some(path) = path {
+                    *action = PersonAction::Walking(path.clone(), Box::new(action.clone()));
+                    *occupants.get_mut(room).unwrap() += 1;
+                }
+            }
+        }
+    }
+
+    /// Samples people passing test centers, analyses as many samples as the centers have
+    /// capacity for, and hands out results once they are ready. Results expire after a while,
+    /// so people can get tested again.
+    fn update_testing(
+        &mut self,
+        index: &SpatialIndex,
+        rng: &mut impl Rng,
+        updates: &mut Vec<StateUpdate>,
+    ) {
+        let config = &self.config.testing;
+        let day = self.tick_count / TICKS_PER_DAY;
+        let capacity = if self.researched(Upgrade::MassTesting) {
+            config.daily_capacity * 2
+        } else {
+            config.daily_capacity
+        };
+        let delay = if self.researched(Upgrade::RapidTests) {
+            config.result_delay_days / 2.0
+        } else {
+            config.result_delay_days
+        };
+
+        for (position, test_center) in &mut self.test_centers {
+            for id in index.at(position) {
+                if self.world.people[id].tested.is_none() && self.awaiting_test.insert(id.clone()) {
+                    test_center.queue.push_back(id.clone());
+                }
+            }
+
+            if test_center.day != day {
+                test_center.day = day;
+                test_center.tested_today = 0;
+            }
+
+            while test_center.tested_today < capacity {
+                let id = match test_center.queue.pop_front() {
+                    Some(id) => id,
+                    None => break,
+                };
+
+                let person = &self.world.people[&id];
+
+                if !person.alive {
+                    self.awaiting_test.remove(&id);
+                    continue;
+                }
+
+                // Tests sometimes miss infections, and sometimes find some that aren't there
+                let positive = if person.infected() {
+                    rng.gen_bool(config.sensitivity)
+                } else {
+                    !rng.gen_bool(config.specificity)
+                };
+
+                test_center.tested_today += 1;
+                self.pending_tests.push(PendingTest {
+                    person: id,
+                    result: if positive {
+                        TestResult::Positive
+                    } else {
+                        TestResult::Negative
+                    },
+                    ready: self.tick_count + days_to_ticks(delay),
+                });
+            }
+        }
+
+        let tick_count = self.tick_count;
+        let (ready, pending) = self
+            .pending_tests
+            .drain(..)
+            .partition::<Vec<_>, _>(|test| test.ready <= tick_count);
+        self.pending_tests = pending;
+
+        for test in ready {
+            let person = self.world.people.get_mut(&test.person).unwrap();
+            person.tested = Some(test.result);
+            person.tick_tested = self.tick_count;
+
+            // Getting tested reminds people to wash their hands
+            person
+                .habits
+                .shift(0.0, self.config.hygiene.testing_boost, 0.0);
+            updates.push(StateUpdate::PersonUpdate(PersonUpdate::attitudes(
+                test.person.clone(),
+                &person.habits,
+            )));
+
+            self.awaiting_test.remove(&test.person);
+            updates.push(StateUpdate::PersonUpdate(PersonUpdate::Tested(
+                test.person,
+                person.tested,
+            )));
+        }
+
+        let valid = days_to_ticks(config.result_valid_days);
+
+        for (id, person) in &mut self.world.people {
+            if person.tested.is_some() && self.tick_count - person.tick_tested > valid {
+                person.tested = None;
+                updates.push(StateUpdate::PersonUpdate(PersonUpdate::Tested(
+                    id.clone(),
+                    person.tested,
+                )));
+            }
+        }
+    }
+
+    /// Infected people leave viral load on the tiles they are on, which slowly decays.
+    /// Others on a contaminated tile can pick it up, unless they wash their hands.
+    fn update_contamination(&mut self, rng: &mut impl Rng, updates: &mut Vec<StateUpdate>) {
+        let config = &self.config.contamination;
+        let handwashing = self.handwashing();
+        let half_life = if self.researched(Upgrade::SurfaceSurvival) {
+            config.half_life_hours * 2.0
+        } else {
+            config.half_life_hours
+        };
+        let decay = 0.5f32.powf(1.0 / (half_life * 60.0));
+        let map = &mut self.world.map;
+
+        for cell in map.contamination.iter_mut().flatten() {
+            cell.load *= decay;
+        }
+
+        // people out of town leave nothing behind, and can't pick anything up
+        let people_actions = &self.people_actions;
+        let people = self
+            .world
+            .people
+            .iter()
+            .filter(|(id, person)| {
+                person.alive && !matches!(people_actions[id], PersonAction::Travelling(_))
+            })
+            .collect::<Vec<_>>();
+
+        for (_, person) in &people {
+            if let Some(strain) = person.strain {
+                let load = config.shedding
+                    * self.world.strains[&strain].transmissibility
+                    * (1.0 - handwashing * person.habits.hygiene);
+
+                // Surfaces in toilets and shops are shared by everyone inside, and touched a
+                // lot more
+                let (tiles, load) = match self.hotspots.get(&person.position) {
+                    Some(hotspot) => {
+                        let tiles = map
+                            .rooms
+                            .get(hotspot)
+                            .into_iter()
+                            .flatten()
+                            .chain(Some(hotspot))
+                            .cloned()
+                            .collect::<Vec<_>>();
+                        let load = load * config.hotspot_multiplier / tiles.len() as f32;
+
+                        (tiles, load)
+                    }
+                    None => (vec![person.position.clone()], load),
+                };
+
+                for tile in tiles {
+                    let cell = &mut map.contamination[tile.x][tile.y];
+                    cell.load += load;
+                    cell.strain = strain;
+                }
+            }
+        }
+
+        let mut infected = Vec::new();
+
+        for (id, person) in &people {
+            let cell = &map.contamination[person.position.x][person.position.y];
+
+            if person.infected() || cell.load <= 0.01 {
+                continue;
+            }
+
+            let strain = &self.world.strains[&cell.strain];
+            let protection = person
+                .immunity
+                .protection(&self.config.immunity, self.tick_count);
+
+            let infection_chance = cell.load
+                * config.infectivity
+                * (1.0 - handwashing * person.habits.hygiene)
+                * (1.0 - protection * (1.0 - strain.vaccine_escape));
+
+            if infection_chance > rng.gen_range(0.0..100.0) {
+                infected.push(((*id).clone(), cell.strain));
+            }
+        }
+
+        for (id, strain) in infected {
+            let person = self.world.people.get_mut(&id).unwrap();
+            person.strain = Some(strain);
+            person.tick_infected = self.tick_count;
+            self.history.infect(id.clone(), None, self.tick_count);
+            updates.push(StateUpdate::PersonUpdate(PersonUpdate::Infected(
+                id,
+                person.strain,
+            )));
+        }
+
+        // the clients only need to see where it is, so don't send it every tick
+        if self.tick_count.is_multiple_of(10) {
+            updates.push(StateUpdate::ContaminationUpdate(
+                self.world.map.contaminated().collect(),
+            ));
+        }
+    }
+
+    /// Spreads attitudes through the acquaintance network every hour, and lets running
+    /// campaigns sway the people around them until they end. The new attitudes are sent
+    /// once an hour.
+    fn update_opinions(&mut self, index: &SpatialIndex, updates: &mut Vec<StateUpdate>) {
+        let config = &self.config.opinion;
+
+        for position in self.campaigns.clone() {
+            let (mask, hygiene, vaccination) = match self.world.map.get_tile(&position) {
+                Tile::MaskCampain(_) => (1.0, 0.5, 0.5),
+                Tile::AntivaxCampain(_) => (-0.5, 0.0, -1.0),
+                Tile::HygieneCampain(_) => (0.0, 1.0, 0.0),
+                _ => continue,
+            };
+            let strength = config.campaign_strength;
+
+            for (id, _) in index.near(&position, config.campaign_radius) {
+                let person = self.world.people.get_mut(id).unwrap();
+                person
+                    .habits
+                    .shift(mask * strength, hygiene * strength, vaccination * strength);
+                self.swayed.insert(id.clone());
+            }
+
+            let tile = &mut self.world.map.tiles[position.x][position.y];
+
+            if let Tile::MaskCampain(time)
+            | Tile::AntivaxCampain(time)
+            | Tile::HygieneCampain(time) = tile
+            {
+                *time = time.saturating_sub(1);
+
+                if *time == 0 {
+                    *tile = Tile::Empty;
+                    self.campaigns.remove(&position);
+                    self.path_cache.invalidate();
+                    updates.push(StateUpdate::TileUpdate(position.clone(), tile.clone()));
+                }
+            }
+        }
+
+        if self.world.time.minutes == 0 {
+            let people = &self.world.people;
+
+            // Everyone takes over part of the average opinion of the acquaintances they
+            // still have
+            let opinions = people
+                .iter()
+                .filter(|(_, person)| person.alive)
+                .filter_map(|(id, person)| {
+                    let others = person
+                        .habits
+                        .acquaintances
+                        .iter()
+                        .map(|other_id| &people[other_id])
+                        .filter(|other| other.alive)
+                        .collect::<Vec<_>>();
+
+                    if others.is_empty() {
+                        return None;
+                    }
+
+                    let count = others.len() as f32;
+                    let average = |f: fn(&Person) -> f32| {
+                        others.iter().map(|other| f(other)).sum::<f32>() / count
+                    };
+
+                    let mask = average(|p| p.habits.mask) - person.habits.mask;
+                    let hygiene = average(|p| p.habits.hygiene) - person.habits.hygiene;
+                    let vaccination =
+                        average(|p| p.habits.vaccination_bias) - person.habits.vaccination_bias;
+
+                    Some((
+                        id.clone(),
+                        mask * config.influence,
+                        hygiene * config.influence,
+                        vaccination * config.influence,
+                    ))
+                })
+                .collect::<Vec<_>>();
+
+            for (id, mask, hygiene, vaccination) in opinions {
+                let person = self.world.people.get_mut(&id).unwrap();
+                person.habits.shift(mask, hygiene, vaccination);
+                self.swayed.insert(id);
+            }
+
+            for id in self.swayed.drain() {
+                updates.push(StateUpdate::PersonUpdate(PersonUpdate::attitudes(
+                    id.clone(),
+                    &self.world.people[&id].habits,
+                )));
+            }
+        }
+    }
+
+    /// Plans the day of everyone alive at midnight.
+    fn update_schedules(&mut self, rng: &mut impl Rng, updates: &mut Vec<StateUpdate>) {
+        let time = &self.world.time;
+
+        if time.hours != 0 || time.minutes != 0 {
+            return;
+        }
+
+        let day = time.days;
+        let schedules = self
+            .world
+            .people
+            .iter()
+            .filter(|(_, person)| person.alive)
+            .map(|(id, person)| {
+                (
+                    id.clone(),
+                    Schedule::generate(person, &self.world, day, rng),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for (id, schedule) in schedules {
+            self.world.people.get_mut(&id).unwrap().schedule = schedule.clone();
+            updates.push(StateUpdate::PersonUpdate(PersonUpdate::Schedule(
+                id, schedule,
+            )));
+        }
+    }
+
+    /// Gives people that lost their job in an economic crash a chance of getting it back, at
+    /// midnight before they plan their day.
+    fn update_layoffs(&mut self, rng: &mut impl Rng, updates: &mut Vec<StateUpdate>) {
+        let time = &self.world.time;
+
+        if time.hours != 0 || time.minutes != 0 {
+            return;
+        }
+
+        let rehire_chance = self.config.economy.rehire_chance;
+        let rehired = self
+            .laid_off
+            .keys()
+            .filter(|_| rng.gen_bool(rehire_chance))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for id in rehired {
+            let job = self.laid_off.remove(&id).unwrap();
+            self.world.people.get_mut(&id).unwrap().job = job.clone();
+            updates.push(StateUpdate::PersonUpdate(PersonUpdate::Job(id, job)));
+        }
+    }
+
+    /// Pays both players from the simulation. The president taxes the output of the people
+    /// at work and supports those in lockdown, the virus earns from every new infection.
+    fn update_economy(&mut self, updates: &mut Vec<StateUpdate>) {
+        let config = &self.config.economy;
+        let lockdown_support = if self.researched(Upgrade::LockdownRelief) {
+            config.lockdown_support / 2.0
+        } else {
+            config.lockdown_support
+        };
+        let mut working = 0;
+        let mut locked_down = 0;
+        let mut new_infections = 0;
+        let mut workforce = 0;
+        let mut unemployed = 0;
+
+        for (id, person) in &self.world.people {
+            if !person.alive {
+                continue;
+            }
+
+            if person.job.ty.in_workforce() {
+                workforce += 1;
+
+                if person.job.ty == JobType::Unemployed {
+                    unemployed += 1;
+                }
+            }
+
+            match self.people_actions[id] {
+                PersonAction::Working if person.job.ty.in_workforce() => working += 1,
+                PersonAction::Lockdown(_) => locked_down += 1,
+                _ => {}
+            }
+
+            if person.infected() && person.tick_infected == self.tick_count {
+                new_infections += 1;
+            }
+        }
+
+        let output = working as f32 * config.output_per_worker;
+        let taxes = output * config.tax_rate - locked_down as f32 * lockdown_support;
+        let bounty = new_infections as f32 * config.infection_bounty;
+
+        for player in [&mut self.player1, &mut self.player2].iter_mut() {
+            let income = if player.side { bounty } else { taxes };
+            player.earn(config.base_income + income);
+        }
+
+        let economy = &mut self.world.economy;
+        economy.output += output;
+        economy.unemployment = unemployed as f32 / workforce.max(1) as f32;
+
+        let time = &self.world.time;
+
+        if time.minutes == 0 {
+            if time.hours == 0 {
+                economy.gdp = economy.output;
+                economy.output = 0.0;
+            }
+
+            updates.push(StateUpdate::EconomyUpdate(economy.clone()));
+        }
+    }
+
+    /// Brings people back home from their travels, some of them having caught the virus
+    /// while they were away.
+    fn update_travel(&mut self, rng: &mut impl Rng, updates: &mut Vec<StateUpdate>) {
+        let now = self.world.time.to_minutes();
+        let calendar = &self.world.calendar;
+
+        for (id, person) in &mut self.world.people {
+            let action = self.people_actions.get_mut(id).unwrap();
+
+            match action {
+                PersonAction::Travelling(until) if *until <= now => {}
+                _ => continue,
+            }
+
+            if person.alive && !person.infected() && rng.gen_bool(calendar.import_chance) {
+                person.strain = self.world.strains.keys().choose(rng).cloned();
+                person.tick_infected = self.tick_count;
+                self.history.infect(id.clone(), None, self.tick_count);
+                updates.push(StateUpdate::PersonUpdate(PersonUpdate::Infected(
+                    id.clone(),
+                    person.strain,
+                )));
+            }
+
+            let path = self.path_cache.get_path(
+                &self.world.map,
+                person.position.clone(),
+                person.home.clone(),
+            );
+
+            if let Some(path) = path {
+                *action = PersonAction::Walking(path.clone(), Box::new(PersonAction::AtHome));
+            }
+        }
+    }
+
+    /// Recalculates how many beds each hospital can use, from the doctors working there that
+    /// are healthy and not stuck at home or in a hospital bed themselves.
+    fn update_hospitals(&mut self, updates: &mut Vec<StateUpdate>) {
+        let mut doctors: HashMap<Position, u32> = HashMap::new();
+
+        for (id, person) in &self.world.people {
+            let available = !matches!(
+                self.people_actions[id],
+                PersonAction::Lockdown(_) | PersonAction::Hospitalized
+            );
+
+            if person.job.ty == JobType::Doctor && person.alive && !person.infected() && available {
+                if let Some(location) = &person.job.location {
+                    *doctors.entry(location.clone()).or_default() += 1;
+                }
+            }
+        }
+
+        for (position, hospital) in &mut self.world.hospitals {
+            let doctors = doctors.get(position).copied().unwrap_or(0);
+            let capacity = (doctors * BEDS_PER_DOCTOR).min(HOSPITAL_BEDS);
+
+            if hospital.capacity != capacity {
+                hospital.capacity = capacity;
+                updates.push(StateUpdate::HospitalUpdate(
+                    position.clone(),
+                    hospital.clone(),
+                ));
+            }
+        }
+    }
+
+    /// Progresses every infection. Some cases show symptoms, some turn severe and need a
+    /// hospital bed, depending on age and comorbidities. When the infection is over the
+    /// person either recovers or dies. Severe cases that could not get a bed are a lot more
+    /// likely to die.
+    fn update_disease(&mut self, rng: &mut impl Rng, updates: &mut Vec<StateUpdate>) {
+        let infected: Vec<PersonId> = self
+            .world
+            .people
+            .iter()
+            .filter(|(_, person)| person.alive && person.infected())
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        let asymptomatic = if self.researched(Upgrade::Asymptomatic) {
+            0.5
+        } else {
+            1.0
+        };
+
+        for id in infected {
+            let person = self.world.people.get_mut(&id).unwrap();
+            let infected_for = self.tick_count - person.tick_infected;
+
+            let config = &self.config.severity;
+            let band = config.band(person.age);
+            let risk = person.risk(config);
+
+            // The older you are the more likely you are to become ill
+            if infected_for == SYMPTOM_ONSET_TICKS {
+                let roll = rng.gen_range(0.0..1.0);
+
+                person.symptoms = if roll < band.hospitalization * risk {
+                    Symptoms::Severe
+                } else if roll < band.symptomatic * asymptomatic {
+                    Symptoms::Mild
+                } else {
+                    Symptoms::None
+                };
+
+                if person.symptoms != Symptoms::None {
+                    updates.push(StateUpdate::PersonUpdate(PersonUpdate::Symptoms(
+                        id.clone(),
+                        person.symptoms,
+                    )));
+                }
+            }
+
+            let hospital = self
+                .world
+                .hospitals
+                .iter_mut()
+                .find(|(_, hospital)| hospital.patients.contains(&id));
+
+            if infected_for > days_to_ticks(self.config.game.recovery_days) {
+                let treated = hospital.is_some();
+
+                if let Some((position, hospital)) = hospital {
+                    hospital.patients.remove(&id);
+                    updates.push(StateUpdate::HospitalUpdate(
+                        position.clone(),
+                        hospital.clone(),
+                    ));
+                }
+
+                let strain = &self.world.strains[&person.strain.unwrap()];
+                let mut mortality = band.fatality * risk * strain.mortality as f64;
+
+                if person.severe() {
+                    mortality *= config.severe_multiplier;
+
+                    if !treated {
+                        mortality *= config.untreated_multiplier;
+                    }
+                }
+
+                if person.symptoms != Symptoms::None {
+                    person.symptoms = Symptoms::None;
+                    updates.push(StateUpdate::PersonUpdate(PersonUpdate::Symptoms(
+                        id.clone(),
+                        person.symptoms,
+                    )));
+                }
+
+                // If true, die, otherwise live.
+                if rng.gen_bool(mortality.min(1.0)) {
+                    person.alive = false;
+                    updates.push(StateUpdate::PersonUpdate(PersonUpdate::LifeStatus(
+                        id.clone(),
+                        person.alive,
+                    )));
+
+                    // Losing someone they know makes people take the virus more seriously
+                    let grief = self.config.opinion.grief;
+
+                    for other_id in person.habits.acquaintances.clone() {
+                        let other = self.world.people.get_mut(&other_id).unwrap();
+
+                        if other.alive {
+                            other.habits.shift(grief, grief, grief);
+                            updates.push(StateUpdate::PersonUpdate(PersonUpdate::attitudes(
+                                other_id,
+                                &other.habits,
+                            )));
+                        }
+                    }
+                } else {
+                    person.strain = None;
+                    person.immunity.tick_recovered = Some(self.tick_count);
+                    updates.push(StateUpdate::PersonUpdate(PersonUpdate::Infected(
+                        id.clone(),
+                        person.strain,
+                    )));
+                    updates.push(StateUpdate::PersonUpdate(PersonUpdate::Immunity(
+                        id.clone(),
+                        person.immunity.clone(),
+                    )));
+
+                    if treated {
+                        let path = self.path_cache.get_path(
+                            &self.world.map,
+                            person.position.clone(),
+                            person.home.clone(),
+                        );
+
+                        if let Some(path) = path {
+                            self.people_actions.insert(
+                                id.clone(),
+                                PersonAction::Walking(path.clone(), Box::new(PersonAction::AtHome)),
+                            );
+                        }
+                    }
+                }
+            } else if person.severe() && hospital.is_none() {
+                // Admit to the closest hospital with a free bed
+                let closest = self
+                    .world
+                    .hospitals
+                    .iter_mut()
+                    .filter(|(_, hospital)| hospital.has_free_bed())
+                    .min_by_key(|(position, _)| {
+                        (position.x as i64 - person.position.x as i64).abs()
+                            + (position.y as i64 - person.position.y as i64).abs()
+                    });
+
+                if let Some((position, hospital)) = closest {
+                    let path = self.path_cache.get_path(
+                        &self.world.map,
+                        person.position.clone(),
+                        position.clone(),
+                    );
+
+                    if let Some(path) = path {
+                        self.people_actions.insert(
+                            id.clone(),
+                            PersonAction::Walking(
+                                path.clone(),
+                                Box::new(PersonAction::Hospitalized),
+                            ),
+                        );
+
+                        hospital.patients.insert(id.clone());
+                        updates.push(StateUpdate::HospitalUpdate(
+                            position.clone(),
+                            hospital.clone(),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    pub async fn handle_players(&mut self, rng: &mut impl Rng) {
         let mut updates = Vec::new();
         let world = &mut self.world;
         let people_actions = &mut self.people_actions;
@@ -275,26 +1106,40 @@ impl GameSession {
         let player1 = &mut self.player1;
         let player2 = &mut self.player2;
         let test_centers = &mut self.test_centers;
+        let awaiting_test = &mut self.awaiting_test;
         let vaccine_centers = &mut self.vaccine_centers;
+        let campaigns = &mut self.campaigns;
+        let booster_campaign = &mut self.booster_campaign;
+        let laid_off = &mut self.laid_off;
+        let history = &mut self.history;
+        let config = &self.config;
+        let tick_count = self.tick_count;
         self.receiver.try_iter().for_each(|update| {
             println!("{:?}", update);
 
             if update.is_valid() {
-                let money = match &update.player {
-                    Player::Player1 => &mut player1.money,
-                    Player::Player2 => &mut player2.money,
+                let player = match &update.player {
+                    Player::Player1 => &mut *player1,
+                    Player::Player2 => &mut *player2,
                 };
 
-                let price = update.command.price_lookup(update.side);
+                let kind = update.command.kind();
+                let price = update.command.price_lookup(update.side, &config.prices);
 
-                if *money < price {
+                if player.money < price || !player.is_ready(kind, tick_count) {
                     return;
                 }
 
-                match &update.command {
-                    PlayerCommand::PartyImpulse(id) => {
-                        *money -= price;
+                // commands aimed at nobody or off the map aren't carried out
+                if !update.command.in_world(world) {
+                    return;
+                }
+
+                let upgrades = &mut player.upgrades;
 
+                // every command tells whether it was carried out, only then is it paid for
+                let carried_out = match &update.command {
+                    PlayerCommand::PartyImpulse(id) => {
                         let person = world.people.get(&id).unwrap();
                         let action = people_actions.get_mut(&id).unwrap();
                         let path = path_cache.get_path(
@@ -325,63 +1170,107 @@ impl GameSession {
                                 );
                             }
                         }
+
+                        true
+                    }
+                    PlayerCommand::Mutate(id, mutation) => {
+                        // only someone alive and infected can start a new strain
+                        let parent = world
+                            .people
+                            .get(id)
+                            .filter(|person| person.alive)
+                            .and_then(|person| person.strain);
+                        let strain = parent.and_then(|parent| {
+                            virus::mutate(&mut world.strains, parent, mutation, rng)
+                        });
+
+                        match (strain, world.people.get_mut(id)) {
+                            (Some(strain), Some(person)) => {
+                                person.strain = Some(strain);
+                                updates.push(StateUpdate::StrainUpdate(
+                                    strain,
+                                    world.strains[&strain].clone(),
+                                ));
+                                updates.push(StateUpdate::PersonUpdate(PersonUpdate::Infected(
+                                    id.clone(),
+                                    person.strain,
+                                )));
+
+                                true
+                            }
+                            _ => false,
+                        }
                     }
                     PlayerCommand::AntivaxCampaign(position) => {
                         if world.map.tiles[position.x][position.y]
                             == update.command.tile_lookup()[0]
                         {
-                            *money -= price;
-
-                            world.map.tiles[position.x][position.y] = Tile::AntivaxCampain(480);
+                            world.map.tiles[position.x][position.y] = Tile::AntivaxCampain(
+                                days_to_ticks(config.opinion.campaign_days) as u32,
+                            );
+                            campaigns.insert(position.clone());
                             updates.push(StateUpdate::TileUpdate(
                                 position.clone(),
                                 world.map.tiles[position.x][position.y].clone(),
                             ));
                             path_cache.invalidate();
+
+                            true
+                        } else {
+                            false
                         }
                     }
                     PlayerCommand::Roadblock(position) => {
                         if world.map.tiles[position.x][position.y]
                             == update.command.tile_lookup()[0]
                         {
-                            *money -= price;
-
                             world.map.tiles[position.x][position.y] = Tile::RoadBlock;
                             updates.push(StateUpdate::TileUpdate(
                                 position.clone(),
                                 world.map.tiles[position.x][position.y].clone(),
                             ));
                             path_cache.invalidate();
+
+                            true
                         } else if world.map.tiles[position.x][position.y]
                             == update.command.tile_lookup()[1]
                         {
-                            *money -= price;
-
                             world.map.tiles[position.x][position.y] = Tile::Empty;
                             updates.push(StateUpdate::TileUpdate(
                                 position.clone(),
                                 world.map.tiles[position.x][position.y].clone(),
                             ));
                             path_cache.invalidate();
+
+                            true
+                        } else {
+                            false
                         }
                     }
                     PlayerCommand::SocialImpulse(position) => {
                         if world.map.tiles[position.x][position.y]
                             == update.command.tile_lookup()[0]
                         {
-                            *money -= price;
-
                             updates.push(StateUpdate::TileUpdate(
                                 position.clone(),
                                 world.map.tiles[position.x][position.y].clone(),
                             ));
                             path_cache.invalidate();
+
+                            true
+                        } else {
+                            false
                         }
                     }
                     PlayerCommand::EconomicCrash => {
-                        *money -= price;
+                        // samples waiting in line are thrown away
+                        for (_, test_center) in test_centers.drain() {
+                            for id in test_center.queue {
+                                awaiting_test.remove(&id);
+                            }
+                        }
 
-                        test_centers.clear();
+                        campaigns.clear();
 
                         for x in 0..world.map.width {
                             for y in 0..world.map.height {
@@ -401,7 +1290,7 @@ impl GameSession {
                                             tile.clone(),
                                         ));
                                     }
-                                    Tile::AntivaxCampain(_) => {
+                                    Tile::AntivaxCampain(_) | Tile::HygieneCampain(_) => {
                                         *tile = Tile::Empty;
                                         updates.push(StateUpdate::TileUpdate(
                                             Position { x, y },
@@ -412,26 +1301,64 @@ impl GameSession {
                                 }
                             }
                         }
+
+                        // businesses lay off some of their workers
+                        let layoffs = world
+                            .people
+                            .iter()
+                            .filter(|(_, person)| person.alive && person.job.location.is_some())
+                            .filter(|(_, person)| person.job.ty.in_workforce())
+                            .filter(|_| rng.gen_bool(config.economy.crash_layoffs))
+                            .map(|(id, _)| id.clone())
+                            .collect::<Vec<_>>();
+
+                        for id in &layoffs {
+                            let person = world.people.get_mut(id).unwrap();
+                            let unemployed = Job {
+                                ty: JobType::Unemployed,
+                                location: None,
+                            };
+
+                            laid_off
+                                .insert(id.clone(), std::mem::replace(&mut person.job, unemployed));
+                            updates.push(StateUpdate::PersonUpdate(PersonUpdate::Job(
+                                id.clone(),
+                                person.job.clone(),
+                            )));
+                        }
+
+                        // the rest of their day is planned without work
+                        for id in layoffs {
+                            let schedule =
+                                Schedule::generate(&world.people[&id], world, world.time.days, rng);
+                            world.people.get_mut(&id).unwrap().schedule = schedule.clone();
+                            updates.push(StateUpdate::PersonUpdate(PersonUpdate::Schedule(
+                                id, schedule,
+                            )));
+                        }
+
+                        true
                     }
                     PlayerCommand::Testcenter(position) => {
                         if world.map.tiles[position.x][position.y]
                             == update.command.tile_lookup()[0]
                         {
-                            *money -= price;
-
                             world.map.tiles[position.x][position.y] = Tile::TestCenter;
                             updates.push(StateUpdate::TileUpdate(
                                 position.clone(),
                                 world.map.tiles[position.x][position.y].clone(),
                             ));
-                            test_centers.insert(position.clone());
+                            test_centers.insert(position.clone(), TestCenter::default());
+
+                            true
+                        } else {
+                            false
                         }
                     }
                     PlayerCommand::Lockdown(position) => {
                         if world.map.tiles[position.x][position.y]
                             == update.command.tile_lookup()[0]
                         {
-                            *money -= price;
                             let mut people_in_lockdown: HashSet<PersonId> = HashSet::new();
 
                             for (id, person) in &world.people {
@@ -441,10 +1368,19 @@ impl GameSession {
                                 }
                             }
 
+                            let resentment = -config.opinion.lockdown_resentment;
+
                             for id in people_in_lockdown {
                                 let person = world.people.get_mut(&id).unwrap();
                                 person.lockdown = true;
 
+                                // Being locked in makes people less willing to go along
+                                person.habits.shift(resentment, resentment, resentment);
+                                updates.push(StateUpdate::PersonUpdate(PersonUpdate::attitudes(
+                                    id.clone(),
+                                    &person.habits,
+                                )));
+
                                 let action = people_actions.get_mut(&id).unwrap();
                                 let path = path_cache.get_path(
                                     &world.map,
@@ -470,14 +1406,16 @@ impl GameSession {
                                 world.map.tiles[position.x][position.y].clone(),
                             ));
                             path_cache.invalidate();
+
+                            true
+                        } else {
+                            false
                         }
                     }
                     PlayerCommand::Vaccinecenter(position) => {
                         if world.map.tiles[position.x][position.y]
                             == update.command.tile_lookup()[0]
                         {
-                            *money -= price;
-
                             world.map.tiles[position.x][position.y] = Tile::VaccineCenter;
                             updates.push(StateUpdate::TileUpdate(
                                 position.clone(),
@@ -485,50 +1423,119 @@ impl GameSession {
                             ));
 
                             vaccine_centers.insert(position.clone());
+
+                            true
+                        } else {
+                            false
+                        }
+                    }
+                    PlayerCommand::BoosterCampaign => {
+                        // a campaign can't be started again before the running one is over
+                        if *booster_campaign <= tick_count {
+                            *booster_campaign =
+                                tick_count + days_to_ticks(config.immunity.booster_campaign_days);
+                            updates.push(StateUpdate::BoosterCampaign(*booster_campaign));
+
+                            true
+                        } else {
+                            false
                         }
                     }
                     PlayerCommand::MaskCampaign(position) => {
                         if world.map.tiles[position.x][position.y]
                             == update.command.tile_lookup()[0]
                         {
-                            *money -= price;
+                            world.map.tiles[position.x][position.y] = Tile::MaskCampain(
+                                days_to_ticks(config.opinion.campaign_days) as u32,
+                            );
+                            campaigns.insert(position.clone());
+                            updates.push(StateUpdate::TileUpdate(
+                                position.clone(),
+                                world.map.tiles[position.x][position.y].clone(),
+                            ));
+                            path_cache.invalidate();
+
+                            true
+                        } else {
+                            false
+                        }
+                    }
+                    PlayerCommand::DisinfectionCrew(position) => {
+                        let map = &mut world.map;
+                        let radius = config.contamination.disinfection_radius;
+                        let right = position.x.saturating_add(radius).min(map.width - 1);
+                        let bottom = position.y.saturating_add(radius).min(map.height - 1);
+
+                        for x in position.x.saturating_sub(radius)..=right {
+                            for y in position.y.saturating_sub(radius)..=bottom {
+                                map.contamination[x][y].load = 0.0;
+                            }
+                        }
 
-                            world.map.tiles[position.x][position.y] = Tile::MaskCampain(480);
+                        true
+                    }
+                    PlayerCommand::HygieneCampaign(position) => {
+                        if world.map.tiles[position.x][position.y]
+                            == update.command.tile_lookup()[0]
+                        {
+                            world.map.tiles[position.x][position.y] = Tile::HygieneCampain(
+                                days_to_ticks(config.opinion.campaign_days) as u32,
+                            );
+                            campaigns.insert(position.clone());
                             updates.push(StateUpdate::TileUpdate(
                                 position.clone(),
                                 world.map.tiles[position.x][position.y].clone(),
                             ));
                             path_cache.invalidate();
+
+                            true
+                        } else {
+                            false
                         }
                     }
+                    PlayerCommand::Research(upgrade) => {
+                        upgrade.unlocked(upgrades) && upgrades.insert(*upgrade)
+                    }
+                };
+
+                // only commands that were carried out are paid for and go on cooldown
+                if carried_out {
+                    player.money -= price;
+                    history.spend(update.side, kind, price);
+
+                    let cooldown = update.command.cooldown_lookup(&config.cooldowns);
+                    player.cooldowns.insert(kind, tick_count + cooldown);
                 }
             }
         });
         self.send_playload(updates).await.unwrap();
     }
 
-    pub async fn is_game_over_and_who_won(&mut self) -> Option<bool> {
-        let mut all_total: u32 = 0;
-        let mut all_infected: u32 = 0;
+    /// How well washing hands works, the virus can research its way around it.
+    pub fn handwashing(&self) -> f32 {
+        if self.researched(Upgrade::SoapResistance) {
+            self.config.hygiene.handwashing / 2.0
+        } else {
+            self.config.hygiene.handwashing
+        }
+    }
 
-        for (_id, person) in &self.world.people {
-            all_total += 1;
-            if person.infected {
-                all_infected += 1;
-            }
+    pub fn player(&self, player: &Player) -> &PlayerSession {
+        match player {
+            Player::Player1 => &self.player1,
+            Player::Player2 => &self.player2,
         }
+    }
 
-        if self.world.time.days > 3 {
-            if all_total as f32 / all_infected as f32 >= 2.0 {
-                self.send_playload(vec![StateUpdate::Winner(true)]).await.unwrap();
-                Some(true)
-            } else {
-                self.send_playload(vec![StateUpdate::Winner(false)]).await.unwrap();
-                Some(false)
-            }
+    /// Whether the side that can research the *upgrade* has done so.
+    pub fn researched(&self, upgrade: Upgrade) -> bool {
+        let player = if self.player1.side == upgrade.side() {
+            &self.player1
         } else {
-            None
-        }
+            &self.player2
+        };
+
+        player.upgrades.contains(&upgrade)
     }
 }
 
@@ -539,29 +1546,76 @@ pub enum Player {
 }
 
 pub struct PlayerSession {
-    socket: OwnedWriteHalf,
-    side: bool,
+    /// Agents have no connection
+    socket: Option<OwnedWriteHalf>,
+    pub side: bool,
     created: bool,
-    money: u32,
+    pub money: u32,
+    /// Income that hasn't added up to a whole unit of money yet.
+    earnings: f32,
+    /// Tick at which each kind of command can be used again.
+    pub cooldowns: HashMap<CommandKind, u64>,
+    pub upgrades: HashSet<Upgrade>,
 }
 
 impl PlayerSession {
-    pub fn create_player(socket: OwnedWriteHalf, side: bool) -> Self {
+    pub fn create_player(socket: Option<OwnedWriteHalf>, side: bool) -> Self {
         PlayerSession {
             socket,
             side,
             created: true,
             money: 0,
+            earnings: 0.0,
+            cooldowns: HashMap::new(),
+            upgrades: HashSet::new(),
         }
     }
+
+    /// Sends a serialized payload to the player, agents have no connection to send it over.
+    pub async fn send(
+        &mut self,
+        payload: &[u8],
+    ) -> Result<(), Box<dyn std::error::Error + 'static + Send + Sync>> {
+        if let Some(socket) = &mut self.socket {
+            let payload_size = (payload.len() as u32).to_be_bytes();
+
+            socket.write_all(&payload_size).await?;
+            socket.write_all(payload).await?;
+        }
+
+        Ok(())
+    }
+
+    pub fn is_ready(&self, kind: CommandKind, tick: u64) -> bool {
+        self.cooldowns.get(&kind).copied().unwrap_or(0) <= tick
+    }
+
+    /// Adds income to the money of the player, a negative income takes money away until
+    /// there is none left.
+    pub fn earn(&mut self, income: f32) {
+        self.earnings += income;
+
+        let whole = self.earnings.floor();
+        self.earnings -= whole;
+        self.money = (self.money as i64 + whole as i64).max(0) as u32;
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum StateUpdate {
-    SetWorld(World),
-    Winner(bool),
+    SetConfig(Box<Config>),
+    SetWorld(Box<World>),
+    EconomyUpdate(Economy),
+    /// The game is over, nothing is sent after this
+    GameOver(Box<GameResult>),
     TileUpdate(Position, Tile),
     PersonUpdate(PersonUpdate),
+    HospitalUpdate(Position, Hospital),
+    StrainUpdate(StrainId, Strain),
+    /// Tick at which the booster campaign ends
+    BoosterCampaign(u64),
+    /// Every tile that is noticeably contaminated, the rest are clean
+    ContaminationUpdate(Vec<(Position, Contamination)>),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -578,6 +1632,10 @@ pub struct NetworkPayload {
     pub side: bool,
     /// The amount of the players owns
     pub money: u32,
+    /// Tick at which each kind of command the player has used can be used again
+    pub cooldowns: HashMap<CommandKind, u64>,
+    /// The upgrades the player has researched
+    pub upgrades: HashSet<Upgrade>,
     /// Vector for PersonUpdate(s)
     pub updates: Vec<StateUpdate>,
 }
@@ -595,6 +1653,8 @@ impl NetworkPayload {
                 .as_secs(),
             side: player_session.side,
             money: player_session.money,
+            cooldowns: player_session.cooldowns.clone(),
+            upgrades: player_session.upgrades.clone(),
             tick_count: session.tick_count,
             age: session.age,
             tick_rate: session.tick_rate,
@@ -614,9 +1674,76 @@ pub enum PlayerCommand {
     Lockdown(Position),   // People in door (building) are stuck
     Vaccinecenter(Position), // Sets Person.vaccine to 1 when person passes position
     MaskCampaign(Position), // Sets Person.habits.mask to 1 when person passses positions
+    HygieneCampaign(Position), // Raises Person.habits.hygiene of people passing by
+    DisinfectionCrew(Position), // Cleans the contamination off every tile around position
+    Mutate(PersonId, Mutation), // Releases a new strain from the infection of the person
+    BoosterCampaign,      // Vaccine centers give booster doses to people whose protection is waning
+    Research(Upgrade),    // Researches an upgrade for the side of the player
+}
+
+/// A player command without its target, commands go on cooldown by their kind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CommandKind {
+    PartyImpulse,
+    AntivaxCampaign,
+    Roadblock,
+    SocialImpulse,
+    EconomicCrash,
+    Testcenter,
+    Lockdown,
+    Vaccinecenter,
+    MaskCampaign,
+    HygieneCampaign,
+    DisinfectionCrew,
+    Mutate,
+    BoosterCampaign,
+    Research,
+}
+
+impl CommandKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            CommandKind::PartyImpulse => "Party Impulse",
+            CommandKind::AntivaxCampaign => "Antivax Campaign",
+            CommandKind::Roadblock => "Roadblock",
+            CommandKind::SocialImpulse => "Social Impulse",
+            CommandKind::EconomicCrash => "Economic Crash",
+            CommandKind::Testcenter => "Testcenter",
+            CommandKind::Lockdown => "Lockdown",
+            CommandKind::Vaccinecenter => "Vaccinecenter",
+            CommandKind::MaskCampaign => "Mask Campaign",
+            CommandKind::HygieneCampaign => "Hygiene Campaign",
+            CommandKind::DisinfectionCrew => "Disinfection Crew",
+            CommandKind::Mutate => "Mutate",
+            CommandKind::BoosterCampaign => "Booster Campaign",
+            CommandKind::Research => "Research",
+        }
+    }
 }
 
 impl PlayerCommand {
+    /// Checks that the person or tile the command is aimed at exists, clients and agents
+    /// can send anything.
+    pub fn in_world(&self, world: &World) -> bool {
+        match self {
+            PlayerCommand::PartyImpulse(id) | PlayerCommand::Mutate(id, _) => {
+                world.people.contains_key(id)
+            }
+            PlayerCommand::AntivaxCampaign(position)
+            | PlayerCommand::Roadblock(position)
+            | PlayerCommand::SocialImpulse(position)
+            | PlayerCommand::Testcenter(position)
+            | PlayerCommand::Lockdown(position)
+            | PlayerCommand::Vaccinecenter(position)
+            | PlayerCommand::MaskCampaign(position)
+            | PlayerCommand::HygieneCampaign(position)
+            | PlayerCommand::DisinfectionCrew(position) => world.map.in_bounds(position),
+            PlayerCommand::EconomicCrash
+            | PlayerCommand::BoosterCampaign
+            | PlayerCommand::Research(_) => true,
+        }
+    }
+
     pub fn is_valid(&self, side: bool) -> bool {
         match self {
             PlayerCommand::PartyImpulse(_) => side == true,
@@ -628,6 +1755,11 @@ impl PlayerCommand {
             PlayerCommand::Lockdown(_) => side == false,
             PlayerCommand::Vaccinecenter(_) => side == false,
             PlayerCommand::MaskCampaign(_) => side == false,
+            PlayerCommand::HygieneCampaign(_) => !side,
+            PlayerCommand::DisinfectionCrew(_) => !side,
+            PlayerCommand::Mutate(_, _) => side,
+            PlayerCommand::BoosterCampaign => !side,
+            PlayerCommand::Research(upgrade) => upgrade.side() == side,
             _ => false,
         }
     }
@@ -643,35 +1775,86 @@ impl PlayerCommand {
             PlayerCommand::Lockdown(_) => &[Tile::Door(Location::Home, None)],
             PlayerCommand::Vaccinecenter(_) => &[Tile::Empty],
             PlayerCommand::MaskCampaign(_) => &[Tile::Empty],
+            PlayerCommand::HygieneCampaign(_) => &[Tile::Empty],
+            PlayerCommand::DisinfectionCrew(_) => &[],
+            PlayerCommand::Mutate(_, _) => &[],
+            PlayerCommand::BoosterCampaign => &[],
+            PlayerCommand::Research(_) => &[],
+        }
+    }
+
+    pub fn kind(&self) -> CommandKind {
+        match self {
+            PlayerCommand::PartyImpulse(_) => CommandKind::PartyImpulse,
+            PlayerCommand::AntivaxCampaign(_) => CommandKind::AntivaxCampaign,
+            PlayerCommand::Roadblock(_) => CommandKind::Roadblock,
+            PlayerCommand::SocialImpulse(_) => CommandKind::SocialImpulse,
+            PlayerCommand::EconomicCrash => CommandKind::EconomicCrash,
+            PlayerCommand::Testcenter(_) => CommandKind::Testcenter,
+            PlayerCommand::Lockdown(_) => CommandKind::Lockdown,
+            PlayerCommand::Vaccinecenter(_) => CommandKind::Vaccinecenter,
+            PlayerCommand::MaskCampaign(_) => CommandKind::MaskCampaign,
+            PlayerCommand::HygieneCampaign(_) => CommandKind::HygieneCampaign,
+            PlayerCommand::DisinfectionCrew(_) => CommandKind::DisinfectionCrew,
+            PlayerCommand::Mutate(_, _) => CommandKind::Mutate,
+            PlayerCommand::BoosterCampaign => CommandKind::BoosterCampaign,
+            PlayerCommand::Research(_) => CommandKind::Research,
         }
     }
 
-    pub fn price_lookup(&self, side: bool) -> u32 {
+    /// Gets the amount of ticks before the command can be used again.
+    pub fn cooldown_lookup(&self, cooldowns: &CooldownConfig) -> u64 {
+        let hours = match self {
+            PlayerCommand::PartyImpulse(_) => cooldowns.party_impulse,
+            PlayerCommand::AntivaxCampaign(_) => cooldowns.antivax_campaign,
+            PlayerCommand::Roadblock(_) => cooldowns.roadblock,
+            PlayerCommand::SocialImpulse(_) => cooldowns.social_impulse,
+            PlayerCommand::EconomicCrash => cooldowns.economic_crash,
+            PlayerCommand::Testcenter(_) => cooldowns.testcenter,
+            PlayerCommand::Lockdown(_) => cooldowns.lockdown,
+            PlayerCommand::Vaccinecenter(_) => cooldowns.vaccinecenter,
+            PlayerCommand::MaskCampaign(_) => cooldowns.mask_campaign,
+            PlayerCommand::HygieneCampaign(_) => cooldowns.hygiene_campaign,
+            PlayerCommand::DisinfectionCrew(_) => cooldowns.disinfection_crew,
+            PlayerCommand::Mutate(_, _) => cooldowns.mutate,
+            PlayerCommand::BoosterCampaign => cooldowns.booster_campaign,
+            PlayerCommand::Research(_) => 0.0,
+        };
+
+        (hours * 60.0) as u64
+    }
+
+    pub fn price_lookup(&self, side: bool, prices: &PriceConfig) -> u32 {
         match self {
-            PlayerCommand::PartyImpulse(_) => 250,
-            PlayerCommand::AntivaxCampaign(_) => 800,
+            PlayerCommand::PartyImpulse(_) => prices.party_impulse,
+            PlayerCommand::AntivaxCampaign(_) => prices.antivax_campaign,
             PlayerCommand::Roadblock(_) => {
                 if side {
-                    150
+                    prices.virus_roadblock
                 } else {
-                    80
+                    prices.president_roadblock
                 }
             }
-            PlayerCommand::SocialImpulse(_) => 180,
-            PlayerCommand::EconomicCrash => 800,
-            PlayerCommand::Testcenter(_) => 300,
-            PlayerCommand::Lockdown(_) => 100,
-            PlayerCommand::Vaccinecenter(_) => 600,
-            PlayerCommand::MaskCampaign(_) => 200,
+            PlayerCommand::SocialImpulse(_) => prices.social_impulse,
+            PlayerCommand::EconomicCrash => prices.economic_crash,
+            PlayerCommand::Testcenter(_) => prices.testcenter,
+            PlayerCommand::Lockdown(_) => prices.lockdown,
+            PlayerCommand::Vaccinecenter(_) => prices.vaccinecenter,
+            PlayerCommand::MaskCampaign(_) => prices.mask_campaign,
+            PlayerCommand::HygieneCampaign(_) => prices.hygiene_campaign,
+            PlayerCommand::DisinfectionCrew(_) => prices.disinfection_crew,
+            PlayerCommand::Mutate(_, _) => prices.mutate,
+            PlayerCommand::BoosterCampaign => prices.booster_campaign,
+            PlayerCommand::Research(upgrade) => upgrade.price(prices),
         }
     }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PlayerUpdate {
-    side: bool,
-    command: PlayerCommand,
-    player: Player,
+    pub side: bool,
+    pub command: PlayerCommand,
+    pub player: Player,
 }
 
 impl PlayerUpdate {
@@ -693,27 +1876,93 @@ async fn server_listener(
         read.read_exact(&mut data).await?;
         let command: PlayerCommand = bincode::deserialize(&data).unwrap();
 
-        sender
+        // the session is gone once the game is over
+        if sender
             .send(PlayerUpdate {
                 side,
                 command,
                 player: player.clone(),
             })
-            .unwrap();
+            .is_err()
+        {
+            return Ok(());
+        }
+    }
+}
+
+/// Makes a player either from their connection, or an agent from the next seat to take
+/// their place.
+fn join_game(
+    player: Player,
+    socket: Option<TcpStream>,
+    side: bool,
+    sender: &Sender<PlayerUpdate>,
+    seats: &mut impl Iterator<Item = Seat>,
+    agents: &mut Vec<AgentPlayer>,
+) -> std::io::Result<PlayerSession> {
+    match socket {
+        Some(socket) => {
+            let (read, write) = socket.into_split();
+            tokio::spawn(server_listener(player, sender.clone(), read, side));
+
+            Ok(PlayerSession::create_player(Some(write), side))
+        }
+        None => {
+            let seat = seats.next().unwrap_or(Seat::Bot(Difficulty::Normal));
+            let agent = seat.agent(side)?;
+            agents.push(AgentPlayer::new(player, side, agent, sender.clone()));
+
+            Ok(PlayerSession::create_player(None, side))
+        }
     }
 }
 
+/// Runs a game between two players until one side wins, players without a connection are
+/// played by the agents of the *seats*. Returns the winning player and their side.
 async fn server_run_game(
-    player1: TcpStream,
-    player2: TcpStream,
-) -> Result<(), Box<dyn std::error::Error + 'static + Send + Sync>> {
+    player1: Option<TcpStream>,
+    player2: Option<TcpStream>,
+    seats: Vec<Seat>,
+    config: Config,
+) -> Result<(Player, bool), Box<dyn std::error::Error + 'static + Send + Sync>> {
     let setting = MapGenerationSettings {
-        width: 24,
-        height: 16,
+        width: config.game.map_width,
+        height: config.game.map_height,
         structures: crate::structures::STRUCTURES,
+        toilets: 12,
     };
 
-    let world = World::generate(setting, &mut rand::thread_rng()); //rngs::StdRng::from_seed([132; 32]));
+    let mut world = World::generate(setting, &config, &mut rand::thread_rng()); //rngs::StdRng::from_seed([132; 32]));
+
+    // Init reusable rng
+    let mut rng = rand::rngs::StdRng::from_seed([rand::thread_rng().gen_range(0..=255); 32]);
+
+    // Plan the first day, the session plans the rest at midnight
+    let schedules = world
+        .people
+        .iter()
+        .map(|(id, person)| (id.clone(), Schedule::generate(person, &world, 0, &mut rng)))
+        .collect::<Vec<_>>();
+
+    for (id, schedule) in schedules {
+        world.people.get_mut(&id).unwrap().schedule = schedule;
+    }
+
+    // Toilets and shops are contamination hotspots, the tiles of a shop share their viral load
+    let shops = world.job_locations.get(&JobType::Clerk).cloned();
+    let hotspots = world
+        .toilets
+        .iter()
+        .map(|toilet| (toilet.clone(), toilet.clone()))
+        .chain(shops.into_iter().flatten().flat_map(|door| {
+            let rooms = world.map.rooms.get(&door).cloned().unwrap_or_default();
+
+            rooms
+                .into_iter()
+                .chain(Some(door.clone()))
+                .map(move |position| (position, door.clone()))
+        }))
+        .collect();
 
     let people_actions = world
         .people
@@ -721,33 +1970,32 @@ async fn server_run_game(
         .map(|id| (id.clone(), PersonAction::AtHome))
         .collect();
 
-    // Init reusable rng
-    let mut rng = rand::rngs::StdRng::from_seed([rand::thread_rng().gen_range(0..=255); 32]);
-
     // Randomly decide sides
     let side = rng.gen_bool(0.5);
 
-    let (player1_read, player1_write) = player1.into_split();
-    let (player2_read, player2_write) = player2.into_split();
+    // Without anyone watching the game runs as fast as it can
+    let headless = player1.is_none() && player2.is_none();
 
     // Init players
-    let player1 = PlayerSession::create_player(player1_write, side);
-    let player2 = PlayerSession::create_player(player2_write, !side);
-
-    // Game logic
     let (sender, receiver) = channel();
-    tokio::spawn(server_listener(
+    let mut seats = seats.into_iter();
+    let mut agents = Vec::new();
+    let player1 = join_game(
         Player::Player1,
-        sender.clone(),
-        player1_read,
-        player1.side,
-    ));
-    tokio::spawn(server_listener(
+        player1,
+        side,
+        &sender,
+        &mut seats,
+        &mut agents,
+    )?;
+    let player2 = join_game(
         Player::Player2,
-        sender,
-        player2_read,
-        player2.side,
-    ));
+        player2,
+        !side,
+        &sender,
+        &mut seats,
+        &mut agents,
+    )?;
 
     let mut session = GameSession {
         player1,
@@ -759,40 +2007,118 @@ async fn server_run_game(
         people_actions,
         path_cache: PathCache::new(),
         receiver,
-        test_centers: HashSet::new(),
+        contacts: HashMap::new(),
+        test_centers: HashMap::new(),
+        pending_tests: Vec::new(),
+        awaiting_test: HashSet::new(),
         vaccine_centers: HashSet::new(),
+        campaigns: HashSet::new(),
+        swayed: HashSet::new(),
+        hotspots,
+        booster_campaign: 0,
+        laid_off: HashMap::new(),
+        history: History::default(),
+        config,
     };
 
+    // The people infected from the start are the first cases
+    for (id, person) in &session.world.people {
+        if person.infected() {
+            session.history.infect(id.clone(), None, session.tick_count);
+        }
+    }
+
     session
-        .send_playload(vec![StateUpdate::SetWorld(session.world.clone())])
+        .send_playload(vec![
+            StateUpdate::SetConfig(Box::new(session.config.clone())),
+            StateUpdate::SetWorld(Box::new(session.world.clone())),
+        ])
         .await?;
 
     loop {
         // Wait a tick before executing the next loop
-        sleep(Duration::from_millis(1000 / session.tick_rate as u64)).await;
+        if !headless {
+            sleep(Duration::from_millis(1000 / session.tick_rate as u64)).await;
+        }
         // Count a tick
         session.tick_count = session.tick_count + 1;
         session.age = session.tick_count / session.tick_rate as u64;
 
+        for agent in &mut agents {
+            if let Some(observation) = agent.observe(&session) {
+                agent.act(observation).await;
+            }
+        }
+
         let updates = session.update(&mut rng).await;
         session.send_playload(updates).await?;
 
-        match session.is_game_over_and_who_won().await {
-            Some(false) => {
-                println!("President won!");
-            }
-            Some(true) => {
-                println!("Virus won!");
-            }
-            _ => {}
+        if let Some(result) = GameResult::check(&session.world, &session.config, &session.history) {
+            println!(
+                "{} won! {}",
+                if result.winner { "Virus" } else { "President" },
+                result.reason.as_str()
+            );
+
+            // The virus is played by whoever got the virus side
+            let winner = match result.winner == side {
+                true => Player::Player1,
+                false => Player::Player2,
+            };
+            let winning_side = result.winner;
+            session
+                .send_playload(vec![StateUpdate::GameOver(Box::new(result))])
+                .await?;
+
+            return Ok((winner, winning_side));
         }
     }
-
-    //Ok((player1, player2))
 }
 
 #[tokio::main]
-pub async fn run(ip: String) -> Result<(), Box<dyn std::error::Error + 'static + Send + Sync>> {
+pub async fn run(
+    ip: String,
+    rules: Option<String>,
+    seats: Vec<Seat>,
+    games: u32,
+) -> Result<(), Box<dyn std::error::Error + 'static + Send + Sync>> {
+    // Load the rules every game is played by
+    let config = match rules {
+        Some(path) => Config::load(&path)?,
+        None => Config::default(),
+    };
+
+    // Agents playing each other don't need anyone to connect
+    if seats.len() >= 2 {
+        let mut virus_wins = 0;
+        // Player 1 takes the first seat and player 2 the second
+        let mut seat_wins = [0; 2];
+
+        for game in 1..=games {
+            print!("Game {}: ", game);
+
+            let (winner, side) = server_run_game(None, None, seats.clone(), config.clone()).await?;
+            if side {
+                virus_wins += 1;
+            }
+            match winner {
+                Player::Player1 => seat_wins[0] += 1,
+                Player::Player2 => seat_wins[1] += 1,
+            }
+        }
+
+        println!(
+            "President won {} games, virus won {}",
+            games - virus_wins,
+            virus_wins
+        );
+        for (number, (seat, wins)) in seats.iter().zip(seat_wins.iter()).enumerate() {
+            println!("Seat {} ({:?}) won {} games", number + 1, seat, wins);
+        }
+
+        return Ok(());
+    }
+
     // Bind server to host and port
     let listener = TcpListener::bind(ip).await?;
 
@@ -800,12 +2126,20 @@ pub async fn run(ip: String) -> Result<(), Box<dyn std::error::Error + 'static +
 
     // Infinite socket loop, at least until two players have connected.
     loop {
-        // Wait until a client tries to connect
+        // Wait until a client tries to connect, an agent takes the place of the second one
         let (player1_socket, _) = listener.accept().await?;
-        let (player2_socket, _) = listener.accept().await?;
+        let player2_socket = match seats.len() {
+            0 => Some(listener.accept().await?.0),
+            _ => None,
+        };
 
         // Start game
-        let game_future = tokio::spawn(server_run_game(player1_socket, player2_socket));
+        let game_future = tokio::spawn(server_run_game(
+            Some(player1_socket),
+            player2_socket,
+            seats.clone(),
+            config.clone(),
+        ));
 
         games.push(game_future);
     }
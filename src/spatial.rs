@@ -0,0 +1,68 @@
+use crate::map::Position;
+use crate::person::PersonId;
+use std::collections::HashMap;
+
+/// Buckets people into square cells, so that finding everyone close to a position only has
+/// to look through the surrounding cells instead of every person.
+pub struct SpatialIndex {
+    cell_size: usize,
+    cells: HashMap<(usize, usize), Vec<(Position, PersonId)>>,
+}
+
+impl SpatialIndex {
+    pub fn new(cell_size: usize) -> Self {
+        Self {
+            cell_size: cell_size.max(1),
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell(&self, position: &Position) -> (usize, usize) {
+        (position.x / self.cell_size, position.y / self.cell_size)
+    }
+
+    pub fn insert(&mut self, position: Position, id: PersonId) {
+        self.cells
+            .entry(self.cell(&position))
+            .or_default()
+            .push((position, id));
+    }
+
+    /// Gets everyone standing exactly on *position*.
+    pub fn at<'a>(&'a self, position: &'a Position) -> impl Iterator<Item = &'a PersonId> {
+        self.cells
+            .get(&self.cell(position))
+            .into_iter()
+            .flatten()
+            .filter(move |(p, _)| p == position)
+            .map(|(_, id)| id)
+    }
+
+    /// Gets everyone within *radius* tiles of *position*, along with their distance.
+    /// Distance is counted in steps, where diagonal steps count as one.
+    pub fn near<'a>(
+        &'a self,
+        position: &'a Position,
+        radius: usize,
+    ) -> impl Iterator<Item = (&'a PersonId, usize)> {
+        let (x, y) = self.cell(position);
+        let cells = radius / self.cell_size + 1;
+
+        (x.saturating_sub(cells)..=x + cells)
+            .flat_map(move |x| (y.saturating_sub(cells)..=y + cells).map(move |y| (x, y)))
+            .filter_map(move |cell| self.cells.get(&cell))
+            .flatten()
+            .filter_map(move |(p, id)| {
+                let distance = (p.x as i64 - position.x as i64)
+                    .abs()
+                    .max((p.y as i64 - position.y as i64).abs())
+                    as usize;
+
+                if distance <= radius {
+                    Some((id, distance))
+                } else {
+                    None
+                }
+            })
+    }
+}
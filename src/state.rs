@@ -1,12 +1,19 @@
+use crate::camera::{Camera, UI_CONSOLE, ZOOM_LEVELS};
 use crate::client::{ClientNetworkHandle, PlayerCommandHandle};
-use crate::map::Position;
-use crate::person::{PersonId, PersonUpdate};
-use crate::server::{PlayerCommand, StateUpdate};
-use crate::ui::{DrawContext, DrawUi, Rect, Ui};
+use crate::config::Config;
+use crate::map::{Position, Tile};
+use crate::overlay::{Overlay, Traffic};
+use crate::person::{Person, PersonId, PersonUpdate, Symptoms};
+use crate::research::Upgrade;
+use crate::score::{GameResult, Sample};
+use crate::server::{CommandKind, PlayerCommand, StateUpdate};
+use crate::ui::{DrawContext, DrawUi, Lines, Rect, Ui, UiMemory};
+use crate::virus::{Mutation, StrainId};
 use crate::world::World;
 use bracket_lib::prelude::*;
 use rand::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 
 pub enum Ability {
     AntivaxCampain,
@@ -16,6 +23,8 @@ pub enum Ability {
     Lockdown,
     Vaccinecenter,
     MaskCampain,
+    HygieneCampain,
+    DisinfectionCrew,
 }
 
 impl Ability {
@@ -28,12 +37,61 @@ impl Ability {
             Ability::Vaccinecenter => "Vaccinecenter",
             Ability::Lockdown => "Lockdown",
             Ability::MaskCampain => "Mask Campaign",
+            Ability::HygieneCampain => "Hygiene Campaign",
+            Ability::DisinfectionCrew => "Disinfection Crew",
         }
     }
 }
 
+/// Tallies of the people, as far as the side can tell.
+#[derive(Default)]
+pub struct Counts {
+    pub cases: u32,
+    pub tested: u32,
+    pub vaccinated: u32,
+    pub dead: u32,
+    pub locked_down: u32,
+}
+
+impl Counts {
+    pub fn new(world: &World, side: bool) -> Self {
+        let mut counts = Self::default();
+
+        for (id, person) in &world.people {
+            if !person.alive {
+                counts.dead += 1;
+                continue;
+            }
+
+            if world.visible_strain(id, side).is_some() {
+                counts.cases += 1;
+            }
+
+            if person.tested.is_some() {
+                counts.tested += 1;
+            }
+
+            if person.immunity.doses > 0 {
+                counts.vaccinated += 1;
+            }
+
+            if matches!(world.map.get_tile(&person.home), Tile::Door(_, Some(_))) {
+                counts.locked_down += 1;
+            }
+        }
+
+        counts
+    }
+}
+
+/// Formats *minutes* as hours and minutes.
+fn duration(minutes: u64) -> String {
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
 pub struct State {
     pub side: bool,
+    /// Size of the view of the map, right of the panel, in ui characters.
     pub width: usize,
     pub height: usize,
     pub money: u32,
@@ -42,7 +100,33 @@ pub struct State {
     pub command_handle: PlayerCommandHandle,
     pub selected_person: Option<PersonId>,
     pub selected_ability: Option<Ability>,
+    /// Door of the building shown in the inspector.
+    pub selected_building: Option<Position>,
     pub person_locations: HashMap<Position, Vec<PersonId>>,
+    pub config: Config,
+    /// Tick at which the current booster campaign ends.
+    pub booster_campaign: u64,
+    /// Colors the map by what the player wants to see.
+    pub overlay: Overlay,
+    pub traffic: Traffic,
+    /// Tick at which each tile was last changed, to tell how long its effect has left.
+    pub tile_changes: HashMap<Position, u64>,
+    /// The part of the map that is shown.
+    pub camera: Camera,
+    /// What the ui remembers from one frame to the next.
+    pub ui_memory: Arc<Mutex<UiMemory>>,
+    /// Name typed into the search field.
+    pub search: String,
+    /// Tick at which each kind of command can be used again.
+    pub cooldowns: HashMap<CommandKind, u64>,
+    pub upgrades: HashSet<Upgrade>,
+    /// Shows the research tree instead of the abilities.
+    pub show_research: bool,
+    /// How the game ended, once it is over.
+    pub result: Option<GameResult>,
+    pub counts: Counts,
+    /// Known cases at the start of every hour along with the tick, oldest first.
+    pub cases: Vec<(u64, u32)>,
 }
 
 impl State {
@@ -56,8 +140,23 @@ impl State {
             handle,
             command_handle,
             selected_person: None,
+            selected_building: None,
             selected_ability: None,
             person_locations: HashMap::new(),
+            config: Config::default(),
+            booster_campaign: 0,
+            overlay: Overlay::None,
+            traffic: Traffic::default(),
+            tile_changes: HashMap::new(),
+            camera: Camera::default(),
+            ui_memory: Default::default(),
+            search: String::new(),
+            cooldowns: HashMap::new(),
+            upgrades: HashSet::new(),
+            show_research: false,
+            result: None,
+            counts: Counts::default(),
+            cases: Vec::new(),
         }
     }
 
@@ -83,6 +182,8 @@ impl State {
 
             self.side = payload.side;
             self.money = payload.money;
+            self.cooldowns = payload.cooldowns;
+            self.upgrades = payload.upgrades;
 
             // TODO: networking stuff with time and stuff
 
@@ -94,8 +195,8 @@ impl State {
                         PersonUpdate::Position(id, new_position) => {
                             self.world.people.get_mut(&id).unwrap().position = new_position;
                         }
-                        PersonUpdate::Infected(id, is_infected) => {
-                            self.world.people.get_mut(&id).unwrap().infected = is_infected;
+                        PersonUpdate::Infected(id, strain) => {
+                            self.world.people.get_mut(&id).unwrap().strain = strain;
                         }
                         PersonUpdate::LifeStatus(id, is_alive) => {
                             self.world.people.get_mut(&id).unwrap().alive = is_alive;
@@ -103,41 +204,110 @@ impl State {
                         PersonUpdate::Habits(id, habits) => {
                             self.world.people.get_mut(&id).unwrap().habits = habits;
                         }
+                        PersonUpdate::Attitudes(id, mask, hygiene, vaccination_bias) => {
+                            let habits = &mut self.world.people.get_mut(&id).unwrap().habits;
+                            habits.mask = mask;
+                            habits.hygiene = hygiene;
+                            habits.vaccination_bias = vaccination_bias;
+                        }
                         PersonUpdate::Tested(id, tested) => {
-                            self.world.people.get_mut(&id).unwrap().tested = tested;
+                            let person = self.world.people.get_mut(&id).unwrap();
+                            person.tested = tested;
+                            person.tick_tested = self.world.time.to_minutes() as u64;
+                        }
+                        PersonUpdate::Immunity(id, immunity) => {
+                            self.world.people.get_mut(&id).unwrap().immunity = immunity;
+                        }
+                        PersonUpdate::Symptoms(id, symptoms) => {
+                            self.world.people.get_mut(&id).unwrap().symptoms = symptoms;
                         }
-                        PersonUpdate::Vaccinated(id, vaccinated) => {
-                            self.world.people.get_mut(&id).unwrap().vaccinated = vaccinated;
+                        PersonUpdate::Job(id, job) => {
+                            self.world.people.get_mut(&id).unwrap().job = job;
+                        }
+                        PersonUpdate::Schedule(id, schedule) => {
+                            self.world.people.get_mut(&id).unwrap().schedule = schedule;
                         }
                     },
                     StateUpdate::TileUpdate(position, tile) => {
+                        self.tile_changes
+                            .insert(position.clone(), self.world.time.to_minutes() as u64);
                         self.world.map.tiles[position.x][position.y] = tile;
                     }
-                    StateUpdate::SetWorld(new_world) => self.world = new_world,
-                    StateUpdate::Winner(winner) => { println!("{} won", winner); panic!(); },
+                    StateUpdate::BoosterCampaign(end) => self.booster_campaign = end,
+                    StateUpdate::ContaminationUpdate(cells) => {
+                        for cell in self.world.map.contamination.iter_mut().flatten() {
+                            *cell = Default::default();
+                        }
+
+                        for (position, contamination) in cells {
+                            self.world.map.contamination[position.x][position.y] = contamination;
+                        }
+                    }
+                    StateUpdate::StrainUpdate(id, strain) => {
+                        self.world.strains.insert(id, strain);
+                    }
+                    StateUpdate::HospitalUpdate(position, hospital) => {
+                        self.world.hospitals.insert(position, hospital);
+                    }
+                    StateUpdate::SetConfig(config) => self.config = *config,
+                    StateUpdate::SetWorld(new_world) => self.world = *new_world,
+                    StateUpdate::EconomyUpdate(economy) => self.world.economy = economy,
+                    StateUpdate::GameOver(result) => self.result = Some(*result),
                 }
             }
+
+            self.counts = Counts::new(&self.world, self.side);
+            self.traffic.update(
+                &self.world,
+                self.config.transmission.contact_radius,
+                payload.tick_count,
+            );
+
+            let tick = payload.tick_count;
+            let sampled = self.cases.last().map(|(last, _)| *last) == Some(tick);
+
+            if self.world.time.minutes == 0 && !sampled {
+                self.cases.push((tick, self.counts.cases));
+            }
         }
     }
 
     const ABILITY_RECT_WIDTH: i32 = 20;
+    /// Hours of cases shown in the side panel.
+    const CASES_CHART_WIDTH: usize = 27;
+    const RESULTS_RECT_WIDTH: i32 = 100;
+    const RESULTS_RECT_HEIGHT: i32 = 56;
+    const RESULTS_COLUMN_WIDTH: i32 = 33;
+    /// Residents or workers shown at a time in the building inspector.
+    const INSPECTOR_ROWS: i32 = 20;
+
+    /// Prints what the command costs, and how long until it can be used again.
+    fn print_cost(&self, ui: &mut Ui, command: PlayerCommand) {
+        ui.print(format!(
+            "Cost: {}",
+            command.price_lookup(self.side, &self.config.prices)
+        ));
+
+        let now = self.world.time.to_minutes() as u64;
+
+        if let Some(ready) = self.cooldowns.get(&command.kind()) {
+            if *ready > now {
+                let left = ready - now;
+                ui.print_color(GREY, format!("Ready in {}h{:02}m", left / 60, left % 60));
+            }
+        }
+    }
 
     pub fn virus_ui(&mut self, ui: &mut Ui) {
         ui.print("VIRUS:");
         ui.print(" Your job is to");
         ui.print(" infect the city.");
 
-        ui.offset(Point::new(0, 1));
-        ui.print("Abilities:");
-
         ui.offset(Point::new(1, 1));
         ui.rect(Self::ABILITY_RECT_WIDTH, 6, |ui| {
             ui.offset(Point::new(1, 1));
             ui.print("Roadblock");
-            ui.print(format!(
-                "Cost: {}",
-                PlayerCommand::Roadblock(Default::default()).price_lookup(self.side)
-            ));
+            self.print_cost(ui, PlayerCommand::Roadblock(Default::default()));
 
             if ui.clicked() {
                 self.selected_ability = Some(Ability::Roadblock);
@@ -148,10 +318,7 @@ impl State {
         ui.rect(Self::ABILITY_RECT_WIDTH, 6, |ui| {
             ui.offset(Point::new(1, 1));
             ui.print("Party Impulse");
-            ui.print(format!(
-                "Cost: {}",
-                PlayerCommand::PartyImpulse(Default::default()).price_lookup(self.side)
-            ));
+            self.print_cost(ui, PlayerCommand::PartyImpulse(Default::default()));
 
             if ui.clicked() {
                 if let Some(selected_person) = &self.selected_person {
@@ -165,10 +332,7 @@ impl State {
         ui.rect(Self::ABILITY_RECT_WIDTH, 6, |ui| {
             ui.offset(Point::new(1, 1));
             ui.print("Social Impulse");
-            ui.print(format!(
-                "Cost: {}",
-                PlayerCommand::SocialImpulse(Default::default()).price_lookup(self.side)
-            ));
+            self.print_cost(ui, PlayerCommand::SocialImpulse(Default::default()));
 
             if ui.clicked() {
                 self.selected_ability = Some(Ability::SocialImpulse);
@@ -179,15 +343,285 @@ impl State {
         ui.rect(Self::ABILITY_RECT_WIDTH, 6, |ui| {
             ui.offset(Point::new(1, 1));
             ui.print("Economic Crash");
-            ui.print(format!(
-                "Cost: {}",
-                PlayerCommand::EconomicCrash.price_lookup(self.side)
-            ));
+            self.print_cost(ui, PlayerCommand::EconomicCrash);
 
             if ui.clicked() {
                 self.command_handle.send(PlayerCommand::EconomicCrash);
             }
         });
+
+        ui.offset(Point::new(0, 1));
+        ui.rect(Self::ABILITY_RECT_WIDTH, 8, |ui| {
+            ui.offset(Point::new(1, 1));
+            ui.print("Mutate");
+            self.print_cost(
+                ui,
+                PlayerCommand::Mutate(Default::default(), Mutation::Transmissibility),
+            );
+
+            for mutation in [
+                Mutation::Transmissibility,
+                Mutation::Mortality,
+                Mutation::VaccineEscape,
+            ]
+            .iter()
+            {
+                ui.text(format!("> {}", mutation.as_str()), |ui| {
+                    if ui.clicked() {
+                        if let Some(selected_person) = &self.selected_person {
+                            self.command_handle.send(PlayerCommand::Mutate(
+                                selected_person.clone(),
+                                mutation.clone(),
+                            ));
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    /// Lists the upgrades of the side, an upgrade is researched by clicking on it once the
+    /// one before it has been researched.
+    pub fn research_ui(&mut self, ui: &mut Ui) {
+        ui.offset(Point::new(1, 0));
+
+        for upgrade in Upgrade::ALL.iter().filter(|u| u.side() == self.side) {
+            let command_handle = &self.command_handle;
+
+            ui.text(format!("> {}", upgrade.as_str()), |ui| {
+                if ui.clicked() {
+                    command_handle.send(PlayerCommand::Research(*upgrade));
+                }
+            });
+
+            ui.offset(Point::new(2, 0));
+
+            match upgrade.requires() {
+                _ if self.upgrades.contains(upgrade) => ui.print_color(GOLD, "Researched"),
+                Some(required) if !self.upgrades.contains(&required) => {
+                    ui.print_color(GREY, format!("Needs {}", required.as_str()))
+                }
+                _ => ui.print_color(
+                    WHITE,
+                    format!(
+                        "{}$ {}",
+                        upgrade.price(&self.config.prices),
+                        upgrade.description()
+                    ),
+                ),
+            }
+
+            ui.offset(Point::new(-2, 1));
+        }
+
+        ui.offset(Point::new(-1, 0));
+    }
+
+    /// Selects the first person whose name contains what was typed into the search field,
+    /// and moves the view to them.
+    pub fn find_person(&mut self) {
+        let search = self.search.to_lowercase();
+
+        let found = self
+            .world
+            .people
+            .iter()
+            .filter(|(_, p)| {
+                format!("{} {}", p.first_name, p.last_name)
+                    .to_lowercase()
+                    .contains(&search)
+            })
+            .min_by_key(|(id, _)| id.0);
+
+        if let Some((id, person)) = found {
+            self.selected_person = Some(id.clone());
+            self.selected_building = None;
+            self.camera.center_on(
+                &person.position,
+                &self.world,
+                self.width as i32,
+                self.height as i32,
+            );
+        }
+    }
+
+    /// Effects on a tile that run out, with the time they have left, and its viral load.
+    pub fn effects(&self, position: &Position) -> Lines {
+        let now = self.world.time.to_minutes() as u64;
+        let changed = self.tile_changes.get(position).copied().unwrap_or(now);
+        let left = |length: u32| (changed + length as u64).saturating_sub(now);
+        let mut lines = Vec::new();
+
+        match self.world.map.get_tile(position) {
+            Tile::Door(_, Some(length)) => match left(*length) {
+                0 => lines.push((ORANGE, "Locked down".to_string())),
+                left => lines.push((ORANGE, format!("Lockdown: {} left", duration(left)))),
+            },
+            Tile::MaskCampain(length)
+            | Tile::AntivaxCampain(length)
+            | Tile::HygieneCampain(length) => {
+                lines.push((GOLD, format!("Campaign: {} left", duration(left(*length)))))
+            }
+            _ => {}
+        }
+
+        let load = self.world.map.contamination[position.x][position.y].load;
+
+        if load > 0.1 {
+            lines.push((ORANGE_RED, format!("Viral load: {:.1}", load)));
+        }
+
+        lines
+    }
+
+    /// What the side knows about a building and the people in it, by its door.
+    pub fn building_info(&self, door: &Position) -> Lines {
+        let world = &self.world;
+        let mut lines = Vec::new();
+
+        if let Tile::Door(location, _) = world.map.get_tile(door) {
+            lines.push((*location.color(), location.name()));
+        }
+
+        let inside = world
+            .map
+            .rooms
+            .get(door)
+            .into_iter()
+            .flatten()
+            .chain(Some(door))
+            .filter_map(|tile| self.person_locations.get(tile))
+            .flatten()
+            .collect::<Vec<_>>();
+        let known = inside
+            .iter()
+            .filter(|id| world.visible_strain(id, self.side).is_some())
+            .count();
+
+        lines.push((GREEN, format!("Inside: {}", inside.len())));
+
+        if known > 0 {
+            lines.push((RED, format!("Known infected inside: {}", known)));
+        }
+
+        let residents = world.people.values().filter(|p| p.home == *door).count();
+        let workers = world
+            .people
+            .values()
+            .filter(|p| p.job.location.as_ref() == Some(door))
+            .count();
+
+        if residents > 0 {
+            lines.push((GREEN, format!("Residents: {}", residents)));
+        }
+
+        if workers > 0 {
+            lines.push((GREEN, format!("Workers: {}", workers)));
+        }
+
+        if let Some(hospital) = world.hospitals.get(door) {
+            lines.push((
+                GREEN,
+                format!(
+                    "Hospital beds: {}/{}",
+                    hospital.patients.len(),
+                    hospital.capacity
+                ),
+            ));
+        }
+
+        lines
+    }
+
+    /// Everything shown when hovering a tile of the map.
+    pub fn tile_info(&self, position: &Position) -> Lines {
+        let world = &self.world;
+        let mut lines = vec![(WHITE, world.map.get_tile(position).as_str().to_string())];
+
+        match world.map.building_at(position) {
+            Some(door) => lines.extend(self.building_info(&door)),
+            None => {
+                let people = self.person_locations.get(position).into_iter().flatten();
+                let (count, known) = people.fold((0, 0), |(count, known), id| {
+                    let visible = world.visible_strain(id, self.side).is_some();
+                    (count + 1, known + visible as u32)
+                });
+
+                if count > 0 {
+                    lines.push((GREEN, format!("People here: {}", count)));
+                }
+
+                if known > 0 {
+                    lines.push((RED, format!("Known infected: {}", known)));
+                }
+            }
+        }
+
+        lines.extend(self.effects(position));
+        lines
+    }
+
+    /// Charts the known cases of the last hours, with counts of what the side knows about
+    /// everyone.
+    pub fn cases_ui(&self, ui: &mut Ui) {
+        ui.print(format!(
+            "{}: {}",
+            if self.side { "Cases" } else { "Known cases" },
+            self.counts.cases
+        ));
+
+        let shown = &self.cases[self.cases.len().saturating_sub(Self::CASES_CHART_WIDTH)..];
+        let max = shown
+            .iter()
+            .map(|(_, cases)| *cases)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let color = if self.side { DARK_RED } else { ORANGE };
+
+        ui.chart(
+            4,
+            max as f32,
+            shown
+                .iter()
+                .map(|(_, cases)| vec![(color, *cases as f32)])
+                .collect(),
+        );
+
+        ui.print(format!(
+            "Tested: {}  Vaccinated: {}",
+            self.counts.tested, self.counts.vaccinated
+        ));
+        ui.print(format!(
+            "Dead: {}  In lockdown: {}",
+            self.counts.dead, self.counts.locked_down
+        ));
+    }
+
+    /// Prints the amount of known cases for every strain.
+    pub fn strains_ui(&self, ui: &mut Ui) {
+        let mut cases: HashMap<StrainId, u32> = HashMap::new();
+
+        for id in self.world.people.keys() {
+            if let Some(strain) = self.world.visible_strain(id, self.side) {
+                *cases.entry(strain).or_default() += 1;
+            }
+        }
+
+        let mut strains = self.world.strains.iter().collect::<Vec<_>>();
+        strains.sort_by_key(|(id, _)| id.0);
+
+        ui.print("Cases per variant:");
+        ui.offset(Point::new(1, 0));
+
+        for (id, strain) in strains {
+            ui.print_color(
+                strain.color,
+                format!("{}: {}", strain.name, cases.get(id).unwrap_or(&0)),
+            );
+        }
+
+        ui.offset(Point::new(-1, 0));
     }
 
     pub fn president_ui(&mut self, ui: &mut Ui) {
@@ -196,17 +630,11 @@ impl State {
         ui.print(" the city from being");
         ui.print(" INFECTED.");
 
-        ui.offset(Point::new(0, 1));
-        ui.print("Abilities:");
-
         ui.offset(Point::new(1, 1));
         ui.rect(Self::ABILITY_RECT_WIDTH, 6, |ui| {
             ui.offset(Point::new(1, 1));
             ui.print("Roadblock");
-            ui.print(format!(
-                "Cost: {}",
-                PlayerCommand::Roadblock(Default::default()).price_lookup(self.side)
-            ));
+            self.print_cost(ui, PlayerCommand::Roadblock(Default::default()));
 
             if ui.clicked() {
                 self.selected_ability = Some(Ability::Roadblock);
@@ -217,24 +645,40 @@ impl State {
         ui.rect(Self::ABILITY_RECT_WIDTH, 6, |ui| {
             ui.offset(Point::new(1, 1));
             ui.print("Mask Campaign");
-            ui.print(format!(
-                "Cost: {}",
-                PlayerCommand::MaskCampaign(Default::default()).price_lookup(self.side)
-            ));
+            self.print_cost(ui, PlayerCommand::MaskCampaign(Default::default()));
 
             if ui.clicked() {
                 self.selected_ability = Some(Ability::MaskCampain);
             }
         });
 
+        ui.offset(Point::new(0, 1));
+        ui.rect(Self::ABILITY_RECT_WIDTH, 6, |ui| {
+            ui.offset(Point::new(1, 1));
+            ui.print("Hygiene Campaign");
+            self.print_cost(ui, PlayerCommand::HygieneCampaign(Default::default()));
+
+            if ui.clicked() {
+                self.selected_ability = Some(Ability::HygieneCampain);
+            }
+        });
+
+        ui.offset(Point::new(0, 1));
+        ui.rect(Self::ABILITY_RECT_WIDTH, 6, |ui| {
+            ui.offset(Point::new(1, 1));
+            ui.print("Disinfection Crew");
+            self.print_cost(ui, PlayerCommand::DisinfectionCrew(Default::default()));
+
+            if ui.clicked() {
+                self.selected_ability = Some(Ability::DisinfectionCrew);
+            }
+        });
+
         ui.offset(Point::new(0, 1));
         ui.rect(Self::ABILITY_RECT_WIDTH, 6, |ui| {
             ui.offset(Point::new(1, 1));
             ui.print("Lockdown");
-            ui.print(format!(
-                "Cost: {}",
-                PlayerCommand::Lockdown(Default::default()).price_lookup(self.side)
-            ));
+            self.print_cost(ui, PlayerCommand::Lockdown(Default::default()));
 
             if ui.clicked() {
                 self.selected_ability = Some(Ability::Lockdown);
@@ -245,10 +689,7 @@ impl State {
         ui.rect(Self::ABILITY_RECT_WIDTH, 6, |ui| {
             ui.offset(Point::new(1, 1));
             ui.print("Testcenter");
-            ui.print(format!(
-                "Cost: {}",
-                PlayerCommand::Testcenter(Default::default()).price_lookup(self.side)
-            ));
+            self.print_cost(ui, PlayerCommand::Testcenter(Default::default()));
 
             if ui.clicked() {
                 self.selected_ability = Some(Ability::Testcenter);
@@ -259,26 +700,239 @@ impl State {
         ui.rect(Self::ABILITY_RECT_WIDTH, 6, |ui| {
             ui.offset(Point::new(1, 1));
             ui.print("Vaccinecenter");
-            ui.print(format!(
-                "Cost: {}",
-                PlayerCommand::Vaccinecenter(Default::default()).price_lookup(self.side)
-            ));
+            self.print_cost(ui, PlayerCommand::Vaccinecenter(Default::default()));
 
             if ui.clicked() {
                 self.selected_ability = Some(Ability::Vaccinecenter);
             }
         });
+
+        ui.offset(Point::new(0, 1));
+        ui.rect(Self::ABILITY_RECT_WIDTH, 7, |ui| {
+            ui.offset(Point::new(1, 1));
+            ui.print("Booster Campaign");
+            self.print_cost(ui, PlayerCommand::BoosterCampaign);
+
+            if self.booster_campaign > self.world.time.to_minutes() as u64 {
+                ui.print_color(GOLD, "Active");
+            }
+
+            if ui.clicked() {
+                self.command_handle.send(PlayerCommand::BoosterCampaign);
+            }
+        });
+    }
+
+    /// Shows who won and how the game went, over the map where it ended.
+    pub fn results_ui(&self, ctx: &mut BTerm, result: &GameResult) {
+        let mut ui = Ui::new(
+            ctx,
+            Rect {
+                position: Point::new(
+                    30 + (self.width as i32 - Self::RESULTS_RECT_WIDTH) / 2,
+                    (self.height as i32 - Self::RESULTS_RECT_HEIGHT) / 2,
+                ),
+                width: Self::RESULTS_RECT_WIDTH,
+                height: Self::RESULTS_RECT_HEIGHT,
+            },
+            self.ui_memory.clone(),
+        );
+        let mut quit = false;
+
+        ui.rect(Self::RESULTS_RECT_WIDTH, Self::RESULTS_RECT_HEIGHT, |ui| {
+            ui.offset(Point::new(1, 1));
+
+            if result.winner {
+                ui.print_color(DARK_RED, "THE VIRUS WINS");
+            } else {
+                ui.print_color(LIGHT_BLUE, "THE PRESIDENT WINS");
+            }
+
+            if result.winner == self.side {
+                ui.print_color(GOLD, "You won!");
+            } else {
+                ui.print_color(GREY, "You lost.");
+            }
+
+            ui.print(result.reason.as_str());
+
+            ui.offset(Point::new(0, 1));
+            let top = ui.offset;
+            ui.print(format!("Days played: {}", result.days));
+            ui.print(format!("Population: {}", result.population));
+            ui.print(format!("Infected at the end: {}", result.infected));
+            ui.print(format!("Recovered: {}", result.recovered));
+            ui.print(format!("Dead: {}", result.deaths));
+
+            ui.set_offset(top + Point::new(Self::RESULTS_COLUMN_WIDTH, 0));
+            ui.print(format!("Tested positive: {}", result.tested_positive));
+            ui.print(format!("Vaccinated: {}", result.vaccinated));
+            ui.print(format!("Variants: {}", result.strains));
+            ui.print(format!("GDP: {:.0}$", result.gdp));
+            ui.print(format!("Unemployment: {:.1}%", result.unemployment * 100.0));
+
+            ui.set_offset(top + Point::new(0, 6));
+            self.epidemic_ui(ui, result);
+
+            ui.offset(Point::new(0, 1));
+            let top = ui.offset;
+            self.spent_ui(ui, result);
+
+            ui.set_offset(top + Point::new(Self::RESULTS_COLUMN_WIDTH, 0));
+            ui.print("Top spreaders:");
+
+            for (id, infected) in result.history.top_spreaders(5) {
+                let person = &self.world.people[&id];
+                ui.print(format!(
+                    " {} {}: {}",
+                    person.first_name, person.last_name, infected
+                ));
+            }
+
+            // long games have more days than fit above the quit button, so they scroll
+            ui.set_offset(top + Point::new(Self::RESULTS_COLUMN_WIDTH * 2, 0));
+            ui.print("R-number by day of infection:");
+
+            let r_numbers = result.history.r_numbers();
+            let height = ui.remaining_height() - 4;
+
+            ui.list("r_numbers", height, r_numbers.len(), |ui, day| {
+                let r = r_numbers[day];
+                ui.print_color(
+                    if r > 1.0 { ORANGE } else { GREEN2 },
+                    format!(" Day {}: {:.2}", day, r),
+                );
+            });
+
+            ui.set_offset(Point::new(top.x, Self::RESULTS_RECT_HEIGHT - 4));
+            quit = ui.button("Quit");
+        });
+
+        let mut ctx = DrawContext { bterm: ctx };
+        ui.draw(&mut ctx);
+
+        if quit {
+            ctx.bterm.quit();
+        }
+    }
+
+    /// Charts how many people were susceptible, infected, recovered and dead over the game.
+    fn epidemic_ui(&self, ui: &mut Ui, result: &GameResult) {
+        let samples = &result.history.samples;
+        let width = (Self::RESULTS_RECT_WIDTH - 4) as usize;
+        // every bar is the average of the samples that fall on it
+        let bars = (0..width.min(samples.len()))
+            .map(|x| {
+                let from = x * samples.len() / width.min(samples.len());
+                let to = ((x + 1) * samples.len() / width.min(samples.len())).max(from + 1);
+                let part = &samples[from..to];
+                let average = |f: fn(&Sample) -> u32| {
+                    part.iter().map(f).sum::<u32>() as f32 / part.len() as f32
+                };
+
+                vec![
+                    (GREY, average(|s| s.dead)),
+                    (RED, average(|s| s.infected)),
+                    (BLUE, average(|s| s.recovered)),
+                    (GREEN, average(|s| s.susceptible)),
+                ]
+            })
+            .collect();
+
+        ui.print("Epidemic over time:");
+        let left = ui.offset.x;
+        ui.chart(16, result.population.max(1) as f32, bars);
+
+        for (color, name) in [
+            (GREEN, "Susceptible"),
+            (BLUE, "Recovered"),
+            (RED, "Infected"),
+            (GREY, "Dead"),
+        ]
+        .iter()
+        {
+            ui.print_color(*color, format!("█ {}", name));
+            ui.offset(Point::new(name.len() as i32 + 4, -1));
+        }
+
+        ui.set_offset(Point::new(left, ui.offset.y + 1));
+    }
+
+    /// Lists the money both sides spent on each kind of command, most first.
+    fn spent_ui(&self, ui: &mut Ui, result: &GameResult) {
+        ui.print("Money spent:");
+
+        let mut spent = result.history.spent.iter().collect::<Vec<_>>();
+        spent.sort_by(|(a, a_amount), (b, b_amount)| {
+            b_amount
+                .cmp(a_amount)
+                .then_with(|| a.1.as_str().cmp(b.1.as_str()))
+        });
+
+        if spent.is_empty() {
+            ui.print_color(GREY, " Nothing");
+        }
+
+        for ((side, kind), amount) in spent {
+            ui.print_color(
+                if *side { DARK_RED } else { LIGHT_BLUE },
+                format!(" {}: {}$", kind.as_str(), amount),
+            );
+        }
     }
 }
 
 impl GameState for State {
     fn tick(&mut self, ctx: &mut BTerm) {
-        ctx.cls_bg(BLACK);
+        // every console is see-through, except for the one of the zoom level in use
+        for console in 0..=UI_CONSOLE {
+            ctx.set_active_console(console);
+            ctx.cls_bg(RGBA::from_u8(0, 0, 0, 0));
+        }
 
         self.handle_payloads();
         self.update_person_locations();
+
+        ctx.set_active_console(UI_CONSOLE);
+        self.ui_memory.lock().unwrap().next_frame(ctx);
+
+        let keyboard = !self.ui_memory.lock().unwrap().has_focus();
+        self.camera.update(
+            ctx,
+            &self.world,
+            keyboard,
+            self.width as i32,
+            self.height as i32,
+        );
+
+        let offset = self.camera.offset();
+        ctx.set_active_console(self.camera.zoom);
+        ctx.cls_bg(BLACK);
         self.world
-            .render(ctx, &self.person_locations, Point::new(30, 0), self.side);
+            .render(ctx, &self.person_locations, offset, self.side);
+        self.overlay.render(
+            ctx,
+            &self.world,
+            &self.person_locations,
+            &self.traffic,
+            self.side,
+            offset,
+        );
+
+        ctx.set_active_console(UI_CONSOLE);
+        self.camera.render_minimap(
+            ctx,
+            &self.world,
+            self.side,
+            self.width as i32,
+            self.height as i32,
+        );
+
+        // nothing can be done once the game is over
+        if let Some(result) = &self.result {
+            self.results_ui(ctx, result);
+            return;
+        }
 
         let mut rng = rand::thread_rng();
 
@@ -289,6 +943,7 @@ impl GameState for State {
                 width: self.width as i32,
                 height: self.height as i32,
             },
+            self.ui_memory.clone(),
         );
 
         ui.rect(30, self.height as i32, |ui| {
@@ -298,16 +953,90 @@ impl GameState for State {
                 "Time: {:#02}:{:#02}:{:#02}",
                 self.world.time.days, self.world.time.hours, self.world.time.minutes
             ));
+            ui.print(format!(
+                "{}{}",
+                self.world.time.weekday().as_str(),
+                match self.world.time.days {
+                    day if self.world.calendar.is_holiday(day) => " (holiday)",
+                    day if self.world.calendar.school_holidays.contains(&day) => {
+                        " (school holiday)"
+                    }
+                    _ => "",
+                }
+            ));
             ui.print(format!("Money: {}$", self.money,));
+            ui.print(format!(
+                "Public trust: {:.0}%",
+                self.world.public_trust() * 100.0
+            ));
+            ui.print(format!("GDP: {:.0}$", self.world.economy.gdp));
+            ui.print(format!("Output today: {:.0}$", self.world.economy.output));
+            ui.print(format!(
+                "Unemployment: {:.1}%",
+                self.world.economy.unemployment * 100.0
+            ));
+            ui.print(format!(
+                "Hospital beds: {}/{}",
+                self.world
+                    .hospitals
+                    .values()
+                    .map(|h| h.patients.len() as u32)
+                    .sum::<u32>(),
+                self.world
+                    .hospitals
+                    .values()
+                    .map(|h| h.capacity)
+                    .sum::<u32>()
+            ));
 
             ui.offset(Point::new(0, 1));
+            self.cases_ui(ui);
 
-            if self.side {
+            ui.offset(Point::new(0, 1));
+
+            if ui.button(format!("Overlay: {}", self.overlay.as_str())) {
+                self.overlay = self.overlay.next();
+            }
+
+            let (width, height) = (self.width as i32, self.height as i32);
+            let mut zoom = self.camera.zoom as i32;
+
+            if ui.slider(
+                format!("Zoom {}x", self.camera.scale()),
+                &mut zoom,
+                0..=ZOOM_LEVELS.len() as i32 - 1,
+            ) {
+                self.camera.set_zoom(zoom as usize, width, height);
+            }
+
+            ui.checkbox("Minimap", &mut self.camera.minimap);
+
+            if ui.text_input("Find:", 22, &mut self.search) {
+                self.find_person();
+            }
+
+            ui.space();
+
+            let mut tab = self.show_research as usize;
+            ui.tabs(&["Abilities", "Research"], &mut tab);
+            self.show_research = tab == 1;
+
+            ui.space();
+
+            if self.show_research {
+                self.research_ui(ui);
+            } else if self.side {
                 self.virus_ui(ui);
             } else {
                 self.president_ui(ui);
             }
 
+            // the president's tests can't tell the strains apart
+            if self.side {
+                ui.offset(Point::new(0, 1));
+                self.strains_ui(ui);
+            }
+
             if let Some(ability) = &self.selected_ability {
                 ui.offset(Point::new(0, 2));
                 ui.print("Selected:");
@@ -316,22 +1045,19 @@ impl GameState for State {
 
             ui.offset(Point::new(0, 2));
             ui.print("Color codes:");
-            ui.offset(Point::new(1, 1));
+            ui.offset(Point::new(1, 0));
             ui.print_color(LIGHT_BLUE, "&: Untested");
-            ui.offset(Point::new(0, 1));
             ui.print_color(GREEN2, "&: Tested");
-            ui.offset(Point::new(0, 1));
             ui.print_color(BLUE, "&: Vaccinated");
-            ui.offset(Point::new(0, 1));
             ui.print_color(ORANGE, "&: Half Infected Group");
-            ui.offset(Point::new(0, 1));
-            ui.print_color(DARK_RED, "&: Infected");
+            ui.print_color(DARK_RED, "&: Infected (variant color)");
         });
 
         ui.set_offset(Point::new(30, 0));
 
         let selected_person = &mut self.selected_person;
         let world = &self.world;
+        let config = &self.config;
 
         if selected_person.is_some() {
             let person = self
@@ -340,7 +1066,7 @@ impl GameState for State {
                 .get(selected_person.as_ref().unwrap())
                 .unwrap();
 
-            ui.rect(30, 40, |ui| {
+            ui.rect(30, 60, |ui| {
                 if ui.mouse_click && !ui.clicked() {
                     *selected_person = None;
                 }
@@ -353,6 +1079,17 @@ impl GameState for State {
                 ui.offset(Point::new(0, 1));
                 ui.print(format!("Age: {}", person.age));
                 ui.offset(Point::new(0, 1));
+
+                if person.comorbidities.is_empty() {
+                    ui.print("Comorbidities: None");
+                } else {
+                    ui.print("Comorbidities:");
+
+                    for comorbidity in &person.comorbidities {
+                        ui.print(format!(" {}", comorbidity.as_str()));
+                    }
+                }
+                ui.offset(Point::new(0, 1));
                 ui.print(format!("Job: {}", person.job.ty.as_str()));
                 ui.offset(Point::new(0, 1));
                 ui.print(format!("Employed: {}", person.job.location.is_some()));
@@ -365,10 +1102,33 @@ impl GameState for State {
                     }
                 ));
                 ui.offset(Point::new(0, 1));
-                ui.print(format!("Tested: {}", person.tested));
+                match &person.tested {
+                    Some(result) => ui.print(format!(
+                        "Tested: {} (day {})",
+                        result.as_str(),
+                        person.tick_tested / crate::config::TICKS_PER_DAY
+                    )),
+                    None => ui.print("Tested: No"),
+                }
                 ui.offset(Point::new(0, 1));
-                ui.print(format!("Vaccinated: {}", person.vaccinated));
+                ui.print(format!("Vaccine doses: {}", person.immunity.doses));
                 ui.offset(Point::new(0, 1));
+                ui.print(format!(
+                    "Immunity: {:.0}%",
+                    person
+                        .immunity
+                        .protection(&config.immunity, world.time.to_minutes() as u64)
+                        * 100.0
+                ));
+                ui.offset(Point::new(0, 1));
+
+                if let Some(recovered) = person.immunity.tick_recovered {
+                    ui.print(format!(
+                        "Recovered: day {}",
+                        recovered / crate::config::TICKS_PER_DAY
+                    ));
+                    ui.offset(Point::new(0, 1));
+                }
 
                 let wears_mask = match person.habits.mask {
                     n if n < 0.1 => "Never",
@@ -379,40 +1139,174 @@ impl GameState for State {
                 };
 
                 ui.print(format!("Wears mask: {}", wears_mask));
+                ui.offset(Point::new(0, 1));
 
-                if person.infected {
+                let washes_hands = match person.habits.hygiene {
+                    n if n < 0.1 => "Never",
+                    n if n < 0.3 => "Rarely",
+                    n if n < 0.6 => "Sometimes",
+                    n if n < 0.9 => "Often",
+                    _ => "Always",
+                };
+
+                ui.print(format!("Washes hands: {}", washes_hands));
+                ui.offset(Point::new(0, 1));
+                ui.print(format!("Trust: {:.0}%", person.habits.trust() * 100.0));
+
+                if let Some(strain) = person.strain.and_then(|s| world.strains.get(&s)) {
+                    ui.offset(Point::new(0, 1));
+                    ui.print_color(strain.color, format!("INFECTED!!! ({})", strain.name));
+                }
+
+                let hospitalized = world
+                    .hospitals
+                    .values()
+                    .any(|h| h.patients.contains(selected_person.as_ref().unwrap()));
+
+                if hospitalized {
                     ui.offset(Point::new(0, 1));
-                    ui.print_color(DARK_RED, "INFECTED!!!");
+                    ui.print_color(DARK_RED, "HOSPITALIZED");
+                } else if person.severe() {
+                    ui.offset(Point::new(0, 1));
+                    ui.print_color(DARK_RED, "SEVERELY ILL, NO BED");
+                } else if person.symptoms == Symptoms::Mild {
+                    ui.offset(Point::new(0, 1));
+                    ui.print_color(ORANGE, "HAS SYMPTOMS");
                 }
 
                 ui.offset(Point::new(0, 1));
-                ui.print("Acquaintances:");
+                ui.print(format!("Schedule (day {}):", person.schedule.day));
                 ui.offset(Point::new(1, 1));
 
-                for aq in &person.habits.acquaintances {
-                    ui.text(
-                        format!(
-                            "{} {}",
-                            world.people[aq].first_name, world.people[aq].last_name
-                        ),
-                        |ui| {
-                            if ui.clicked() {
-                                *selected_person = Some(aq.clone());
-                            }
-                        },
+                let locked_down = matches!(
+                    world.map.tiles[person.home.x][person.home.y],
+                    Tile::Door(_, Some(_))
+                );
+                let minute = world.time.hours * 60 + world.time.minutes;
+
+                if locked_down {
+                    ui.print_color(ORANGE, "Plans cancelled, in lockdown");
+                }
+
+                for plan in &person.schedule.plans {
+                    let text = format!(
+                        "{:02}:{:02}-{:02}:{:02} {}",
+                        plan.start / 60,
+                        plan.start % 60,
+                        plan.end / 60,
+                        plan.end % 60,
+                        plan.activity.as_str()
                     );
+
+                    if (plan.start..plan.end).contains(&minute) {
+                        ui.print_color(YELLOW, text);
+                    } else {
+                        ui.print(text);
+                    }
+                }
+
+                ui.offset(Point::new(-1, 0));
+
+                ui.space();
+                ui.print("Acquaintances:");
+                ui.offset(Point::new(1, 1));
+
+                let mut acquaintances = person
+                    .habits
+                    .acquaintances
+                    .iter()
+                    .map(|aq| {
+                        let other = &world.people[aq];
+                        (aq, format!("{} {}", other.first_name, other.last_name))
+                    })
+                    .collect::<Vec<_>>();
+                acquaintances.sort_by(|(_, a), (_, b)| a.cmp(b));
+
+                let height = ui.remaining_height();
+
+                let id = format!("acquaintances of {}", selected_person.as_ref().unwrap().0);
+
+                ui.list(&id, height, acquaintances.len(), |ui, i| {
+                    let (aq, name) = &acquaintances[i];
+
+                    ui.text(name, |ui| {
+                        if ui.clicked() {
+                            *selected_person = Some((*aq).clone());
+                        }
+                    });
+                });
+            });
+        }
+
+        if let Some(door) = self.selected_building.clone() {
+            let mut lines = self.building_info(&door);
+            lines.extend(self.effects(&door));
+
+            let world = &self.world;
+            let people = |at_building: &dyn Fn(&Person) -> bool| {
+                let mut people = world
+                    .people
+                    .iter()
+                    .filter(|(_, p)| at_building(p))
+                    .map(|(id, p)| (id.clone(), format!("{} {}", p.first_name, p.last_name)))
+                    .collect::<Vec<_>>();
+                people.sort_by(|(_, a), (_, b)| a.cmp(b));
+                people
+            };
+            let residents = people(&|p| p.home == door);
+            let workers = people(&|p| p.job.location.as_ref() == Some(&door));
+
+            let selected_building = &mut self.selected_building;
+            let selected_person = &mut self.selected_person;
+
+            ui.rect(30, 60, |ui| {
+                if ui.mouse_click && !ui.clicked() {
+                    *selected_building = None;
+                }
+
+                ui.print("Building:");
+                ui.offset(Point::new(1, 1));
+
+                for (color, line) in &lines {
+                    ui.print_color(*color, line);
+                }
+
+                for (title, people) in [("Residents", &residents), ("Workers", &workers)].iter() {
+                    if people.is_empty() {
+                        continue;
+                    }
+
+                    ui.space();
+                    ui.print(format!("{}:", title));
+
+                    let height = (people.len() as i32).min(Self::INSPECTOR_ROWS);
+
+                    ui.indent(|ui| {
+                        let id = format!("{} of {:?}", title, door);
+
+                        ui.list(&id, height, people.len(), |ui, i| {
+                            let (id, name) = &people[i];
+
+                            ui.text(name, |ui| {
+                                if ui.clicked() {
+                                    *selected_person = Some(id.clone());
+                                    *selected_building = None;
+                                }
+                            });
+                        });
+                    });
                 }
             });
         }
 
         if ui.mouse_click && self.selected_person.is_none() {
-            if ctx.mouse_point().x >= 30 {
+            if let Some(position) = self.camera.tile_at(
+                ctx.mouse_point(),
+                &self.world,
+                self.width as i32,
+                self.height as i32,
+            ) {
                 if let Some(ability) = &self.selected_ability {
-                    let position = Position::new(
-                        ctx.mouse_point().x as usize - 30,
-                        ctx.mouse_point().y as usize,
-                    );
-
                     match ability {
                         Ability::AntivaxCampain => {
                             self.command_handle
@@ -440,14 +1334,21 @@ impl GameState for State {
                             self.command_handle
                                 .send(PlayerCommand::MaskCampaign(position));
                         }
+                        Ability::HygieneCampain => {
+                            self.command_handle
+                                .send(PlayerCommand::HygieneCampaign(position));
+                        }
+                        Ability::DisinfectionCrew => {
+                            self.command_handle
+                                .send(PlayerCommand::DisinfectionCrew(position));
+                        }
                     }
                 } else {
-                    if let Some(persons) = self.person_locations.get(&Position::new(
-                        ctx.mouse_point().x as usize - 30,
-                        ctx.mouse_point().y as usize,
-                    )) {
+                    if let Some(persons) = self.person_locations.get(&position) {
+                        self.selected_building = None;
+
                         if self.side {
-                            let infected = persons.iter().filter(|p| self.world.people[p].infected);
+                            let infected = persons.iter().filter(|p| self.world.people[p].infected());
 
                             self.selected_person = Some(
                                 infected
@@ -458,6 +1359,8 @@ impl GameState for State {
                         } else {
                             self.selected_person = Some(persons.choose(&mut rng).unwrap().clone());
                         }
+                    } else {
+                        self.selected_building = self.world.map.building_at(&position);
                     }
                 }
 
@@ -465,6 +1368,18 @@ impl GameState for State {
             }
         }
 
+        // the panels cover part of the map, so there is nothing to hover while they are open
+        if self.selected_person.is_none() && self.selected_building.is_none() {
+            if let Some(position) = self.camera.tile_at(
+                ctx.mouse_point(),
+                &self.world,
+                self.width as i32,
+                self.height as i32,
+            ) {
+                ui.tooltip(ctx.mouse_point(), self.tile_info(&position));
+            }
+        }
+
         let mut ctx = DrawContext { bterm: ctx };
         ui.draw(&mut ctx);
     }
@@ -1,4 +1,6 @@
 use bracket_lib::prelude::*;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
 use std::sync::{Arc, Mutex};
 
 pub struct UiRect {
@@ -23,6 +25,7 @@ impl DrawUi for UiRect {
 
 pub struct UiPrint {
     color: (u8, u8, u8),
+    background: (u8, u8, u8),
     rect: Rect,
     text: String,
 }
@@ -33,12 +36,93 @@ impl DrawUi for UiPrint {
             self.rect.position.x,
             self.rect.position.y,
             self.color,
-            BLACK,
+            self.background,
             &self.text,
         );
     }
 }
 
+/// The colored parts of a bar in a chart, from the bottom up.
+pub type Bar = Vec<((u8, u8, u8), f32)>;
+
+/// Bars stacked from the bottom up, one column for every bar.
+pub struct UiChart {
+    rect: Rect,
+    bars: Vec<Bar>,
+    /// Value that fills the whole height of the chart
+    max: f32,
+}
+
+impl DrawUi for UiChart {
+    fn draw(&self, ctx: &mut DrawContext) {
+        let height = self.rect.height as f32;
+        let bottom = self.rect.position.y + self.rect.height - 1;
+
+        for (x, bar) in self.bars.iter().enumerate() {
+            let mut total = 0.0;
+
+            for (color, value) in bar {
+                let from = (total / self.max * height).round() as i32;
+                total += value;
+                let to = (total / self.max * height).round().min(height) as i32;
+
+                for y in from..to {
+                    ctx.bterm.set(
+                        self.rect.position.x + x as i32,
+                        bottom - y,
+                        *color,
+                        BLACK,
+                        to_cp437('█'),
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Lines of text, each with its color.
+pub type Lines = Vec<((u8, u8, u8), String)>;
+
+/// A box of colored lines next to a point, moved to the other side of it where it would
+/// go off the console.
+pub struct UiTooltip {
+    at: Point,
+    lines: Lines,
+}
+
+impl DrawUi for UiTooltip {
+    fn draw(&self, ctx: &mut DrawContext) {
+        let (console_width, console_height) = ctx.bterm.get_char_size();
+        let width = self
+            .lines
+            .iter()
+            .map(|(_, line)| line.len())
+            .max()
+            .unwrap_or(0) as i32
+            + 2;
+        let height = self.lines.len() as i32 + 2;
+
+        let mut x = self.at.x + 1;
+        let mut y = self.at.y + 1;
+
+        if x + width > console_width as i32 {
+            x = self.at.x - width;
+        }
+
+        if y + height > console_height as i32 {
+            y = self.at.y - height;
+        }
+
+        ctx.bterm
+            .draw_box(x, y, width - 1, height - 1, GREEN, BLACK);
+
+        for (i, (color, line)) in self.lines.iter().enumerate() {
+            ctx.bterm
+                .print_color(x + 1, y + 1 + i as i32, *color, BLACK, line);
+        }
+    }
+}
+
 pub trait DrawUi {
     fn draw(&self, ctx: &mut DrawContext);
 }
@@ -54,22 +138,120 @@ pub struct DrawContext<'a> {
     pub bterm: &'a mut BTerm,
 }
 
+/// What the ui remembers from one frame to the next, as the ui itself is built anew every
+/// frame.
+#[derive(Default)]
+pub struct UiMemory {
+    /// The widget with the keyboard focus, by the order the focusable widgets are added in
+    focus: Option<usize>,
+    /// Focusable widgets added so far this frame
+    focusable: usize,
+    /// Focusable widgets added in the last frame
+    last_focusable: usize,
+    /// Rows each scrollable list is scrolled down, by its id
+    scroll: HashMap<String, usize>,
+    /// Key pressed this frame, for the widget with the focus
+    key: Option<VirtualKeyCode>,
+    /// Characters typed this frame
+    typed: Vec<char>,
+    /// Whether the left mouse button was down the last frame
+    mouse_down: bool,
+}
+
+impl UiMemory {
+    /// Starts a new frame with the input since the last one. Tab and shift tab move the focus
+    /// between widgets, escape or clicking somewhere else lets go of it.
+    pub fn next_frame(&mut self, ctx: &mut BTerm) {
+        let mut input = INPUT.lock();
+
+        // with advanced input the window doesn't close by itself, but the characters typed
+        // are only sent as events
+        self.typed.clear();
+        while let Some(event) = input.pop() {
+            match event {
+                BEvent::Character { c } if !c.is_control() => self.typed.push(c),
+                BEvent::CloseRequested => ctx.quit(),
+                _ => {}
+            }
+        }
+
+        self.last_focusable = self.focusable;
+        self.focusable = 0;
+        self.key = ctx.key;
+
+        // left_click is set when the button is let go of as well
+        if ctx.left_click && input.is_mouse_button_pressed(0) {
+            self.focus = None;
+        }
+
+        match ctx.key {
+            Some(VirtualKeyCode::Tab) if self.last_focusable > 0 => {
+                let count = self.last_focusable;
+
+                self.focus = Some(match (self.focus, ctx.shift) {
+                    (Some(focus), false) => (focus + 1) % count,
+                    (Some(focus), true) => (focus + count - 1) % count,
+                    (None, false) => 0,
+                    (None, true) => count - 1,
+                });
+                self.key = None;
+            }
+            Some(VirtualKeyCode::Escape) => {
+                self.focus = None;
+                self.key = None;
+            }
+            _ => {}
+        }
+
+        // the widget may be gone since the last frame
+        if self.focus >= Some(self.last_focusable) {
+            self.focus = None;
+        }
+    }
+
+    /// Whether a widget takes the keyboard, the keys are left to the rest of the game
+    /// otherwise.
+    pub fn has_focus(&self) -> bool {
+        self.focus.is_some()
+    }
+}
+
 pub struct Ui {
     pub mouse_point: Point,
+    /// The left mouse button was pressed this frame
     pub mouse_click: bool,
+    /// The left mouse button is held down
+    pub mouse_down: bool,
+    /// The left mouse button was let go of this frame
+    pub mouse_release: bool,
     pub offset: Point,
     pub rect: Rect,
     pub drawables: Vec<Box<dyn DrawUi>>,
+    memory: Arc<Mutex<UiMemory>>,
 }
 
 impl Ui {
-    pub fn new(ctx: &BTerm, rect: Rect) -> Self {
+    /// Cells in the track of a [`Ui::slider`] at most, ranges with more values share cells.
+    const SLIDER_WIDTH: i32 = 16;
+
+    pub fn new(ctx: &BTerm, rect: Rect, memory: Arc<Mutex<UiMemory>>) -> Self {
+        let mouse_down = INPUT.lock().is_mouse_button_pressed(0);
+        let mouse_release = {
+            let mut memory = memory.lock().unwrap();
+            let release = memory.mouse_down && !mouse_down;
+            memory.mouse_down = mouse_down;
+            release
+        };
+
         Self {
             mouse_point: ctx.mouse_point(),
-            mouse_click: INPUT.lock().is_mouse_button_pressed(0) && ctx.left_click,
+            mouse_click: mouse_down && ctx.left_click,
+            mouse_down,
+            mouse_release,
             offset: Point::new(0, 0),
             rect,
             drawables: Vec::new(),
+            memory,
         }
     }
 
@@ -77,6 +259,8 @@ impl Ui {
         Ui {
             mouse_point: self.mouse_point,
             mouse_click: self.mouse_click,
+            mouse_down: self.mouse_down,
+            mouse_release: self.mouse_release,
             rect: Rect {
                 position: self.rect.position + self.offset.clone(),
                 width,
@@ -84,6 +268,7 @@ impl Ui {
             },
             offset,
             drawables: Vec::new(),
+            memory: self.memory.clone(),
         }
     }
 
@@ -119,6 +304,7 @@ impl Ui {
     pub fn print(&mut self, text: impl Into<String>) {
         self.drawables.push(Box::new(UiPrint {
             color: GREEN,
+            background: BLACK,
             rect: self.get_rect(),
             text: text.into(),
         }));
@@ -128,6 +314,7 @@ impl Ui {
     pub fn print_color(&mut self, color: (u8, u8, u8), text: impl Into<String>) {
         self.drawables.push(Box::new(UiPrint {
             color,
+            background: BLACK,
             rect: self.get_rect(),
             text: text.into(),
         }));
@@ -141,15 +328,343 @@ impl Ui {
 
         self.drawables.push(Box::new(UiPrint {
             color: GREEN,
+            background: BLACK,
             rect: self.get_rect(),
             text: text,
         }));
         self.offset(Point::new(0, 1));
     }
 
+    /// Draws a bar chart *height* rows tall, where *max* fills a whole bar.
+    pub fn chart(&mut self, height: i32, max: f32, bars: Vec<Bar>) {
+        self.drawables.push(Box::new(UiChart {
+            rect: Rect {
+                position: self.get_rect().position,
+                width: bars.len() as i32,
+                height,
+            },
+            bars,
+            max,
+        }));
+        self.offset(Point::new(0, height));
+    }
+
+    /// Leaves an empty line.
+    pub fn space(&mut self) {
+        self.offset(Point::new(0, 1));
+    }
+
+    /// Adds what *f* adds one character further in.
+    pub fn indent(&mut self, mut f: impl FnMut(&mut Ui)) {
+        self.offset(Point::new(1, 0));
+        f(self);
+        self.offset(Point::new(-1, 0));
+    }
+
+    /// Lines left until the bottom of the rect, inside its border.
+    pub fn remaining_height(&self) -> i32 {
+        (self.rect.height - self.offset.y - 1).max(0)
+    }
+
+    /// Numbers the next focusable widget, which is focused by clicking on it when
+    /// *click_focus* is set. Returns whether it has the focus, along with the key pressed for
+    /// it if it has.
+    fn focus(&self, hovered: bool, click_focus: bool) -> (bool, Option<VirtualKeyCode>) {
+        let mut memory = self.memory.lock().unwrap();
+        let id = memory.focusable;
+        memory.focusable += 1;
+
+        if click_focus && hovered && self.mouse_click {
+            memory.focus = Some(id);
+        }
+
+        let focused = memory.focus == Some(id);
+        (focused, if focused { memory.key } else { None })
+    }
+
+    /// Colors of a widget, brighter when hovered and inverted while pressed.
+    fn style(&self, hovered: bool, focused: bool) -> ((u8, u8, u8), (u8, u8, u8)) {
+        match (hovered, self.mouse_down, focused) {
+            (true, true, _) => (BLACK, GREEN),
+            (true, false, _) => (LIGHT_GREEN, DARK_GREEN),
+            (false, _, true) => (YELLOW, BLACK),
+            _ => (GREEN, BLACK),
+        }
+    }
+
+    /// Adds *text* at a column of this line, with its own colors.
+    fn label(&mut self, column: i32, colors: ((u8, u8, u8), (u8, u8, u8)), text: String) {
+        self.drawables.push(Box::new(UiPrint {
+            color: colors.0,
+            background: colors.1,
+            rect: Rect {
+                position: self.get_rect().position + Point::new(column, 0),
+                width: text.len() as i32,
+                height: 1,
+            },
+            text,
+        }));
+    }
+
+    /// Whether the mouse is over *width* characters of this line, from *column* on.
+    fn hovers(&self, column: i32, width: i32) -> bool {
+        let position = self.get_rect().position + Point::new(column, 0);
+
+        self.mouse_point.y == position.y
+            && self.mouse_point.x >= position.x
+            && self.mouse_point.x < position.x + width
+    }
+
+    /// A button that is pressed by letting go of the mouse on it, or with enter or space
+    /// while it has the focus.
+    pub fn button(&mut self, text: impl Into<String>) -> bool {
+        let text = format!("[{}]", text.into());
+        let hovered = self.hovers(0, text.len() as i32);
+        let (focused, key) = self.focus(hovered, false);
+
+        let pressed = (hovered && self.mouse_release)
+            || matches!(
+                key,
+                Some(VirtualKeyCode::Return) | Some(VirtualKeyCode::Space)
+            );
+
+        self.label(0, self.style(hovered, focused), text);
+        self.offset(Point::new(0, 1));
+
+        pressed
+    }
+
+    /// A box in front of *text* that toggles *value*, returns whether it changed.
+    pub fn checkbox(&mut self, text: impl Into<String>, value: &mut bool) -> bool {
+        let text = format!("[{}] {}", if *value { 'x' } else { ' ' }, text.into());
+        let hovered = self.hovers(0, text.len() as i32);
+        let (focused, key) = self.focus(hovered, false);
+
+        let toggled = (hovered && self.mouse_release)
+            || matches!(
+                key,
+                Some(VirtualKeyCode::Return) | Some(VirtualKeyCode::Space)
+            );
+
+        if toggled {
+            *value = !*value;
+        }
+
+        self.label(0, self.style(hovered, focused), text);
+        self.offset(Point::new(0, 1));
+
+        toggled
+    }
+
+    /// Picks *value* out of *range* by clicking or dragging along a track after *text*, or
+    /// with the left and right arrows while it has the focus. Returns whether it changed.
+    pub fn slider(
+        &mut self,
+        text: impl Into<String>,
+        value: &mut i32,
+        range: RangeInclusive<i32>,
+    ) -> bool {
+        let text = format!("{} ", text.into());
+        let (min, max) = (*range.start(), *range.end());
+        let steps = (max - min).max(1);
+        let cells = (steps + 1).min(Self::SLIDER_WIDTH);
+        let start = text.len() as i32 + 1;
+
+        let hovered = self.hovers(start, cells);
+        let (focused, key) = self.focus(hovered, true);
+        let old = *value;
+
+        if hovered && self.mouse_down {
+            let cell = self.mouse_point.x - self.get_rect().position.x - start;
+            *value = min + cell * steps / (cells - 1).max(1);
+        }
+
+        match key {
+            Some(VirtualKeyCode::Left) => *value -= 1,
+            Some(VirtualKeyCode::Right) => *value += 1,
+            _ => {}
+        }
+
+        *value = (*value).max(min).min(max);
+
+        let handle = (*value - min) * (cells - 1) / steps;
+        let track = (0..cells)
+            .map(|cell| if cell == handle { '█' } else { '─' })
+            .collect::<String>();
+        let colors = self.style(hovered, focused);
+
+        self.label(0, (colors.0, BLACK), text);
+        self.label(start - 1, colors, format!("[{}]", track));
+        self.offset(Point::new(0, 1));
+
+        *value != old
+    }
+
+    /// A row of tabs with the *selected* one highlighted, the left and right arrows move
+    /// between them while the row has the focus. Returns whether another tab was picked.
+    pub fn tabs(&mut self, tabs: &[&str], selected: &mut usize) -> bool {
+        let hovered = self.hovers(0, tabs.iter().map(|tab| tab.len() as i32 + 3).sum());
+        let (focused, key) = self.focus(hovered, false);
+        let old = *selected;
+        let mut column = 0;
+
+        for (i, tab) in tabs.iter().enumerate() {
+            let width = tab.len() as i32 + 2;
+            let tab_hovered = self.hovers(column, width);
+
+            if tab_hovered && self.mouse_release {
+                *selected = i;
+            }
+
+            let colors = if i == *selected {
+                (BLACK, if focused { YELLOW } else { GREEN })
+            } else {
+                self.style(tab_hovered, false)
+            };
+
+            self.label(column, colors, format!(" {} ", tab));
+            column += width + 1;
+        }
+
+        match key {
+            Some(VirtualKeyCode::Left) => *selected = selected.saturating_sub(1),
+            Some(VirtualKeyCode::Right) => *selected = (*selected + 1).min(tabs.len() - 1),
+            _ => {}
+        }
+
+        self.offset(Point::new(0, 1));
+
+        *selected != old
+    }
+
+    /// A field *width* characters wide after *text*, that *value* is typed into once it has
+    /// been clicked on. Returns whether enter was pressed in it.
+    pub fn text_input(&mut self, text: impl Into<String>, width: i32, value: &mut String) -> bool {
+        let text = format!("{} ", text.into());
+        let start = text.len() as i32;
+        let hovered = self.hovers(start, width);
+        let (focused, key) = self.focus(hovered, true);
+
+        if focused {
+            let memory = self.memory.lock().unwrap();
+
+            for c in &memory.typed {
+                if (value.len() as i32) < width - 1 {
+                    value.push(*c);
+                }
+            }
+
+            if key == Some(VirtualKeyCode::Back) {
+                value.pop();
+            }
+        }
+
+        let shown = if focused {
+            format!("{}█", value)
+        } else {
+            value.clone()
+        };
+        let background = if focused || hovered {
+            DARK_GREEN
+        } else {
+            (20, 40, 20)
+        };
+
+        self.label(0, (GREEN, BLACK), text);
+        self.label(
+            start,
+            (GREEN, background),
+            format!("{:width$}", shown, width = width as usize),
+        );
+        self.offset(Point::new(0, 1));
+
+        focused && key == Some(VirtualKeyCode::Return)
+    }
+
+    /// A list of *rows* lines, *height* of them shown at a time with a scrollbar to the right
+    /// of them. Only the rows in view are added, by *f* with their index, and each must be one
+    /// line high. The list is scrolled with the arrows of the scrollbar, or the up and down
+    /// and page keys once it has been clicked on.
+    pub fn list(&mut self, id: &str, height: i32, rows: usize, mut f: impl FnMut(&mut Ui, usize)) {
+        let height = height.max(3);
+        let width = self.rect.width - self.offset.x - 1;
+        let top = self.get_rect().position;
+        let hovered = self.mouse_point.x >= top.x
+            && self.mouse_point.x < top.x + width
+            && self.mouse_point.y >= top.y
+            && self.mouse_point.y < top.y + height;
+        let (focused, key) = self.focus(hovered, true);
+
+        let bar = width - 1;
+        let shown = height as usize;
+        let max_scroll = rows.saturating_sub(shown);
+
+        let mut memory = self.memory.lock().unwrap();
+        let scroll = memory.scroll.entry(id.to_string()).or_default();
+
+        if self.mouse_click && self.mouse_point.x == top.x + bar {
+            match self.mouse_point.y - top.y {
+                0 => *scroll = scroll.saturating_sub(1),
+                y if y == height - 1 => *scroll += 1,
+                y if y > 0 && y < height - 1 => {
+                    *scroll = (y as usize - 1) * max_scroll / (shown - 2).max(1)
+                }
+                _ => {}
+            }
+        }
+
+        match key {
+            Some(VirtualKeyCode::Up) => *scroll = scroll.saturating_sub(1),
+            Some(VirtualKeyCode::Down) => *scroll += 1,
+            Some(VirtualKeyCode::PageUp) => *scroll = scroll.saturating_sub(shown),
+            Some(VirtualKeyCode::PageDown) => *scroll += shown,
+            _ => {}
+        }
+
+        *scroll = (*scroll).min(max_scroll);
+        let scroll = *scroll;
+        drop(memory);
+
+        let start = self.offset;
+
+        for index in scroll..rows.min(scroll + shown) {
+            self.set_offset(start + Point::new(0, (index - scroll) as i32));
+            f(self, index);
+        }
+
+        if rows > shown {
+            let color = if focused { YELLOW } else { GREEN };
+            let handle = 1 + scroll * (shown - 3) / max_scroll.max(1);
+
+            for y in 0..height {
+                let glyph = match y as usize {
+                    0 => "↑",
+                    y if y == shown - 1 => "↓",
+                    y if y == handle => "█",
+                    _ => "│",
+                };
+
+                self.set_offset(start + Point::new(bar, y));
+                self.label(0, (color, BLACK), glyph.to_string());
+            }
+        }
+
+        self.set_offset(start + Point::new(0, height));
+    }
+
+    /// Shows *lines* in a box next to *at*, on top of what has been added to the ui before.
+    pub fn tooltip(&mut self, at: Point, lines: Lines) {
+        if !lines.is_empty() {
+            self.drawables.push(Box::new(UiTooltip { at, lines }));
+        }
+    }
+
     pub fn clicked(&self) -> bool {
-        self.mouse_click
-            && self.mouse_point.x >= self.rect.position.x
+        self.mouse_click && self.hovered()
+    }
+
+    pub fn hovered(&self) -> bool {
+        self.mouse_point.x >= self.rect.position.x
             && self.mouse_point.x <= self.rect.position.x + self.rect.width
             && self.mouse_point.y >= self.rect.position.y
             && self.mouse_point.y <= self.rect.position.y + self.rect.height
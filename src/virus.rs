@@ -0,0 +1,139 @@
+use bracket_lib::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Maximum amount of strains that can exist in a single game.
+pub const MAX_STRAINS: usize = 12;
+
+const STRAIN_NAMES: &[&str] = &[
+    "Original", "Alpha", "Beta", "Gamma", "Delta", "Epsilon", "Zeta", "Eta", "Theta", "Iota",
+    "Kappa", "Lambda",
+];
+
+const STRAIN_COLORS: &[(u8, u8, u8)] = &[
+    DARK_RED,
+    MAGENTA,
+    CRIMSON,
+    DARK_ORCHID,
+    HOT_PINK,
+    CHOCOLATE,
+    MEDIUMVIOLETRED,
+    SALMON,
+    PURPLE,
+    FIREBRICK1,
+    DEEP_PINK,
+    SIENNA,
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StrainId(pub u32);
+
+impl StrainId {
+    /// Stands in for the strain of a case the president knows about, tests can't tell
+    /// strains apart and don't know whether their result is right.
+    pub const UNKNOWN: StrainId = StrainId(u32::MAX);
+}
+
+/// The trait a mutation is focused on.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Mutation {
+    Transmissibility,
+    Mortality,
+    VaccineEscape,
+}
+
+impl Mutation {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Mutation::Transmissibility => "Transmissibility",
+            Mutation::Mortality => "Mortality",
+            Mutation::VaccineEscape => "Vaccine Escape",
+        }
+    }
+
+    pub fn generate(rng: &mut impl Rng) -> Self {
+        match rng.gen_range(0..3) {
+            0 => Mutation::Transmissibility,
+            1 => Mutation::Mortality,
+            2 => Mutation::VaccineEscape,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Strain {
+    pub name: String,
+    pub color: (u8, u8, u8),
+    pub parent: Option<StrainId>,
+    /// Multiplier on the chance of infecting someone.
+    pub transmissibility: f32,
+    /// Multiplier on the chance of dying from the infection.
+    pub mortality: f32,
+    /// Share of the vaccine protection the strain gets around, between 0 and 1.
+    pub vaccine_escape: f32,
+}
+
+impl Strain {
+    pub fn original() -> Self {
+        Self {
+            name: STRAIN_NAMES[0].to_string(),
+            color: STRAIN_COLORS[0],
+            parent: None,
+            transmissibility: 1.0,
+            mortality: 1.0,
+            vaccine_escape: 0.0,
+        }
+    }
+
+    /// Creates strain *id* from this one (*parent*), where the *mutation* trait is strengthened
+    /// and the rest drift slightly.
+    pub fn mutate(
+        &self,
+        parent: StrainId,
+        id: StrainId,
+        mutation: &Mutation,
+        rng: &mut impl Rng,
+    ) -> Self {
+        let index = id.0 as usize % MAX_STRAINS;
+
+        let mut strain = Self {
+            name: STRAIN_NAMES[index].to_string(),
+            color: STRAIN_COLORS[index],
+            parent: Some(parent),
+            transmissibility: self.transmissibility * rng.gen_range(0.9..1.1),
+            mortality: self.mortality * rng.gen_range(0.9..1.1),
+            vaccine_escape: self.vaccine_escape,
+        };
+
+        match mutation {
+            Mutation::Transmissibility => strain.transmissibility *= rng.gen_range(1.2..1.6),
+            Mutation::Mortality => strain.mortality *= rng.gen_range(1.3..2.0),
+            Mutation::VaccineEscape => {
+                strain.vaccine_escape += (1.0 - strain.vaccine_escape) * rng.gen_range(0.2..0.5)
+            }
+        }
+
+        strain
+    }
+}
+
+/// Adds a new strain mutated from *parent* and returns its id, unless there already are
+/// [`MAX_STRAINS`] strains.
+pub fn mutate(
+    strains: &mut HashMap<StrainId, Strain>,
+    parent: StrainId,
+    mutation: &Mutation,
+    rng: &mut impl Rng,
+) -> Option<StrainId> {
+    if strains.len() >= MAX_STRAINS {
+        return None;
+    }
+
+    let id = StrainId(strains.len() as u32);
+    let strain = strains[&parent].mutate(parent, id, mutation, rng);
+    strains.insert(id, strain);
+
+    Some(id)
+}
@@ -1,30 +1,43 @@
+use crate::config::Config;
 use crate::map::{Map, Position, Tile};
 use crate::map_generation::MapGenerationSettings;
 use crate::person::{self, Person, PersonId};
+use crate::virus::{Strain, StrainId};
 use bracket_lib::prelude::*;
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub enum Location {
     Home,
     Job(person::JobType),
+    Gym,
 }
 
 impl Location {
     pub fn generate(rng: &mut impl Rng) -> Self {
-        match rng.gen_range(0..2) {
-            0 => Location::Home,
-            1 => Location::Job(person::JobType::generate(rng)),
+        match rng.gen_range(0..12) {
+            0..=5 => Location::Home,
+            6..=10 => Location::Job(person::JobType::generate(rng)),
+            11 => Location::Gym,
             _ => unreachable!(),
         }
     }
 
+    pub fn name(&self) -> String {
+        match self {
+            Location::Home => "Home".to_string(),
+            Location::Job(ty) => format!("Workplace ({})", ty.as_str()),
+            Location::Gym => "Gym".to_string(),
+        }
+    }
+
     pub fn color(&self) -> &(u8, u8, u8) {
         match self {
             Location::Home => &DARKOLIVEGREEN4,
             Location::Job(_) => &BURLYWOOD,
+            Location::Gym => &STEEL_BLUE,
         }
     }
 }
@@ -33,7 +46,7 @@ impl Location {
 pub struct Time {
     pub minutes: u32,
     pub hours: u32,
-    pub days: u8,
+    pub days: u32,
 }
 
 impl Time {
@@ -51,37 +64,180 @@ impl Time {
         self.hours = (self.minutes / 60) as u32;
         self.minutes = self.minutes % 60;
 
-        self.days = (self.hours / 24) as u8;
+        self.days = self.hours / 24;
         self.hours = self.hours % 24;
     }
 
     pub fn to_minutes(&self) -> u32 {
-        self.minutes + self.hours * 60 + self.days as u32 * 60 * 24
+        self.minutes + self.hours * 60 + self.days * 60 * 24
+    }
+
+    pub fn weekday(&self) -> Weekday {
+        Weekday::from_day(self.days)
+    }
+}
+
+/// The first day of the game is a monday.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    pub fn from_day(day: u32) -> Self {
+        match day % 7 {
+            0 => Weekday::Monday,
+            1 => Weekday::Tuesday,
+            2 => Weekday::Wednesday,
+            3 => Weekday::Thursday,
+            4 => Weekday::Friday,
+            5 => Weekday::Saturday,
+            6 => Weekday::Sunday,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn is_weekend(&self) -> bool {
+        matches!(self, Weekday::Saturday | Weekday::Sunday)
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Weekday::Monday => "Monday",
+            Weekday::Tuesday => "Tuesday",
+            Weekday::Wednesday => "Wednesday",
+            Weekday::Thursday => "Thursday",
+            Weekday::Friday => "Friday",
+            Weekday::Saturday => "Saturday",
+            Weekday::Sunday => "Sunday",
+        }
+    }
+}
+
+/// Public holidays, school holidays and what people do with their days off.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Calendar {
+    /// Days, counted from the start of the game, that are public holidays.
+    pub holidays: Vec<u32>,
+    /// Days the schools are closed on top of the weekends and public holidays.
+    pub school_holidays: Vec<u32>,
+    /// Chance of a person leaving town on a holiday.
+    pub travel_chance: f64,
+    /// Chance of a person coming back from their travels infected.
+    pub import_chance: f64,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Self {
+            holidays: vec![2, 10],
+            // schools close for the rest of the week after the holiday
+            school_holidays: vec![3, 4],
+            travel_chance: 0.2,
+            import_chance: 0.05,
+        }
+    }
+}
+
+impl Calendar {
+    pub fn is_holiday(&self, day: u32) -> bool {
+        self.holidays.contains(&day)
+    }
+
+    /// Weekends and holidays are days off for most people.
+    pub fn is_day_off(&self, day: u32) -> bool {
+        Weekday::from_day(day).is_weekend() || self.is_holiday(day)
+    }
+
+    /// Schools are also closed during the school holidays.
+    pub fn is_school_day_off(&self, day: u32) -> bool {
+        self.is_day_off(day) || self.school_holidays.contains(&day)
+    }
+}
+
+/// Number of beds in every hospital, regardless of staffing.
+pub const HOSPITAL_BEDS: u32 = 20;
+/// Number of beds a single healthy doctor can look after.
+pub const BEDS_PER_DOCTOR: u32 = 5;
+
+/// A hospital is the door of a building where doctors work.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Default)]
+pub struct Hospital {
+    /// Beds that can currently be used, limited by the doctors available.
+    pub capacity: u32,
+    pub patients: HashSet<PersonId>,
+}
+
+impl Hospital {
+    pub fn has_free_bed(&self) -> bool {
+        (self.patients.len() as u32) < self.capacity
     }
 }
 
+/// The state of the economy, driven by the people at work.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Default)]
+pub struct Economy {
+    /// Value produced by the people at work during the previous day.
+    pub gdp: f32,
+    /// Value produced so far today.
+    pub output: f32,
+    /// Share of the workforce without a job.
+    pub unemployment: f32,
+}
+
 /// Incapsulates the entire simulated world.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct World {
     pub time: Time,
+    pub calendar: Calendar,
     pub map: Map,
+    #[serde(with = "crate::map::by_position")]
     pub locations: HashMap<Position, Location>,
     pub people: HashMap<person::PersonId, person::Person>,
     pub job_locations: HashMap<person::JobType, Vec<Position>>,
+    #[serde(with = "crate::map::by_position")]
+    pub hospitals: HashMap<Position, Hospital>,
+    pub strains: HashMap<StrainId, Strain>,
+    pub toilets: Vec<Position>,
+    pub economy: Economy,
 }
 
 impl World {
+    /// Walkable tiles along the edge of the map, where people can leave town.
+    pub fn exits(&self) -> Vec<Position> {
+        let (width, height) = (self.map.width, self.map.height);
+
+        (0..width)
+            .flat_map(|x| vec![Position::new(x, 0), Position::new(x, height - 1)])
+            .chain((0..height).flat_map(|y| vec![Position::new(0, y), Position::new(width - 1, y)]))
+            .filter(|p| self.map.can_walk(p))
+            .collect()
+    }
+
     pub fn empty(chunks_w: usize, chunks_h: usize) -> Self {
         Self {
             time: Time::new(),
+            calendar: Calendar::default(),
             map: Map::fill(chunks_w * 6, chunks_h * 6, Tile::Empty),
             locations: HashMap::new(),
             people: HashMap::new(),
             job_locations: HashMap::new(),
+            hospitals: HashMap::new(),
+            strains: HashMap::new(),
+            toilets: Vec::new(),
+            economy: Economy::default(),
         }
     }
 
-    pub fn generate(settings: MapGenerationSettings, rng: &mut impl Rng) -> Self {
+    pub fn generate(settings: MapGenerationSettings, config: &Config, rng: &mut impl Rng) -> Self {
+        let population = &config.population;
         let map = settings.generate(rng);
 
         // generate locations, from doors
@@ -97,6 +253,11 @@ impl World {
             })
             .collect::<HashMap<_, _>>();
 
+        let toilets = (0..map.width)
+            .flat_map(|x| (0..map.height).map(move |y| Position::new(x, y)))
+            .filter(|p| *map.get_tile(p) == Tile::Toilet)
+            .collect();
+
         let mut jobs = HashMap::new();
 
         // find all homes and collect references
@@ -110,32 +271,54 @@ impl World {
                         .push(position.clone());
                     None
                 }
+                Location::Gym => None,
             })
             .collect::<HashMap<_, _>>();
 
-        // generate people with random homes
-        let mut people: HashMap<person::PersonId, person::Person> = (0..400)
-            .into_iter()
-            .map(|id| {
-                let home = homes.keys().choose(rng).unwrap().clone();
-                let id = person::PersonId(id);
+        // generate people, adults get random homes and children move in with them
+        let mut ages = (0..population.size)
+            .map(|_| population.generate_age(rng))
+            .collect::<Vec<_>>();
+        ages.sort_unstable_by(|a, b| b.cmp(a));
 
-                homes.get_mut(&home).unwrap().push(id.clone());
+        let mut people: HashMap<person::PersonId, person::Person> = HashMap::new();
+        let mut adult_homes: Vec<Position> = Vec::new();
 
-                let job_type = person::JobType::generate(rng);
+        for (id, age) in ages.into_iter().enumerate() {
+            let id = person::PersonId(id as u32);
 
-                let job_location = jobs
-                    .get(&job_type)
-                    .map(|locations| locations.choose(rng).unwrap().clone());
+            let home = match adult_homes.choose(rng) {
+                Some(home) if age < population.working_age => home.clone(),
+                _ => homes.keys().choose(rng).unwrap().clone(),
+            };
 
-                let job = person::Job {
-                    ty: job_type,
-                    location: job_location,
-                };
+            if age >= population.working_age {
+                adult_homes.push(home.clone());
+            }
 
-                (id, person::Person::generate(rng, home, job))
-            })
-            .collect();
+            homes.get_mut(&home).unwrap().push(id.clone());
+
+            let job_type = population.generate_job_type(age, rng);
+
+            // schools are where the teachers work
+            let workplace = match job_type {
+                person::JobType::Student => person::JobType::Teacher,
+                _ => job_type.clone(),
+            };
+
+            // people without a job have no workplace
+            let job_location = job_type
+                .shift()
+                .and_then(|_| jobs.get(&workplace))
+                .map(|locations| locations.choose(rng).unwrap().clone());
+
+            let job = person::Job {
+                ty: job_type,
+                location: job_location,
+            };
+
+            people.insert(id, person::Person::generate(rng, home, age, job));
+        }
 
         let ids = people.keys().map(|k| k.clone()).collect::<Vec<_>>();
 
@@ -156,16 +339,72 @@ impl World {
             }
         }
 
-        for _ in 0..10 {
-            people.values_mut().choose(rng).unwrap().infected = true;
+        for _ in 0..config.game.initial_infections {
+            people.values_mut().choose(rng).unwrap().strain = Some(StrainId(0));
         }
 
+        // every building doctors work in is a hospital
+        let hospitals = jobs
+            .get(&person::JobType::Doctor)
+            .map(|positions| {
+                positions
+                    .iter()
+                    .map(|position| (position.clone(), Hospital::default()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
         Self {
             time: Time::new(),
+            calendar: config.calendar.clone(),
             map,
             locations,
             people,
             job_locations: jobs,
+            hospitals,
+            strains: vec![(StrainId(0), Strain::original())]
+                .into_iter()
+                .collect(),
+            toilets,
+            economy: Economy::default(),
+        }
+    }
+
+    /// Gets the strain a person is infected with, if the *side* is able to see it.
+    /// The virus knows about every infection, the president only about people that tested
+    /// positive, false positives included, and never which strain they have.
+    pub fn visible_strain(&self, id: &PersonId, side: bool) -> Option<StrainId> {
+        let person = &self.people[id];
+
+        if side {
+            person.strain
+        } else if person.tested_positive() {
+            Some(StrainId::UNKNOWN)
+        } else {
+            None
+        }
+    }
+
+    /// The public toilet closest to *position*, if there are any.
+    pub fn nearest_toilet(&self, position: &Position) -> Option<&Position> {
+        self.toilets.iter().min_by_key(|toilet| {
+            (toilet.x as i64 - position.x as i64).abs()
+                + (toilet.y as i64 - position.y as i64).abs()
+        })
+    }
+
+    /// Average trust in the public health measures among the living, from 0 to 1.
+    pub fn public_trust(&self) -> f32 {
+        let alive = self.people.values().filter(|p| p.alive);
+
+        let (count, trust) = alive.fold((0, 0.0), |(count, trust), person| {
+            (count + 1, trust + person.habits.trust())
+        });
+
+        if count == 0 {
+            0.0
+        } else {
+            trust / count as f32
         }
     }
 
@@ -181,19 +420,33 @@ impl World {
         for (location, persons) in person_locations {
             let sick = persons
                 .iter()
-                .map(|p| (self.people[p].infected && (side || self.people[p].tested)) as i32 as f32)
+                .map(|p| self.visible_strain(p, side).is_some() as i32 as f32)
                 .sum::<f32>()
                 / persons.len() as f32;
 
+            // color fully infected groups by the most common strain among them
+            let mut strain_counts: HashMap<StrainId, usize> = HashMap::new();
+
+            for strain in persons.iter().filter_map(|p| self.visible_strain(p, side)) {
+                *strain_counts.entry(strain).or_default() += 1;
+            }
+
+            let strain_color = strain_counts
+                .iter()
+                .max_by_key(|(_, count)| **count)
+                .and_then(|(strain, _)| self.strains.get(strain))
+                .map(|strain| strain.color)
+                .unwrap_or(DARK_RED);
+
             let tested = persons
                 .iter()
-                .map(|p| self.people[p].tested as i32 as f32)
+                .map(|p| self.people[p].tested.is_some() as i32 as f32)
                 .sum::<f32>()
                 / persons.len() as f32;
 
             let vaccinated = persons
                 .iter()
-                .map(|p| self.people[p].vaccinated as i32 as f32)
+                .map(|p| self.people[p].immunity.vaccinated() as i32 as f32)
                 .sum::<f32>()
                 / persons.len() as f32;
 
@@ -217,14 +470,11 @@ impl World {
                 n if n == 0.0 => vaccinated_color,
                 n if n < 0.5 => ORANGE2,
                 n if n < 1.0 => ORANGE,
-                n if n == 1.0 => DARK_RED,
+                n if n == 1.0 => strain_color,
                 _ => unreachable!(),
             };
 
-            let location = Position::new(
-                location.x + offset.x as usize,
-                location.y + offset.y as usize,
-            );
+            let location = Point::new(location.x, location.y) + offset;
 
             match persons.len() {
                 1 => ctx.print_color(location.x, location.y, color, BLACK, "&"),
@@ -234,3 +484,18 @@ impl World {
         }
     }
 }
+
+#[cfg(test)]
+impl World {
+    /// A town generated the same way the server does it, from a fixed seed.
+    pub fn generate_for_test(config: &Config, seed: u64) -> Self {
+        let settings = MapGenerationSettings {
+            width: config.game.map_width,
+            height: config.game.map_height,
+            structures: crate::structures::STRUCTURES,
+            toilets: 12,
+        };
+
+        Self::generate(settings, config, &mut StdRng::seed_from_u64(seed))
+    }
+}